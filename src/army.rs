@@ -12,6 +12,8 @@ const UNITS: &[UID] = &[
 ];
 const COMBAT_UNITS: &[UID] = &[UID::Marine, UID::Hellion, UID::Cyclone];
 const SUPPORT_UNITS: &[UID] = &[UID::Medivac];
+/// Against a rush the army holds the ramp until this game time, unless maxed out
+const RUSH_DEFENSE_TIME: f32 = 360.0;
 
 trait Addon {
     fn is_addon_for(&self, structure: UID) -> bool;
@@ -33,9 +35,11 @@ impl TerranBot {
         if self.time > END_OF_BUILD_PRIO {
             self.train_army();
         }
+        self.scout();
         self.scout_and_harass();
         self.move_idle_army();
         self.move_active_army();
+        self.escort_army_with_ravens();
     }
 
     fn train_army(&mut self) {
@@ -68,7 +72,7 @@ impl TerranBot {
                     }
                     let unit = if self.counter().count(UID::Reaper) < 1
                         && self.time < 180.0
-                        && self.can_afford(UnitTypeId::Reaper, false)
+                        && self.can_afford_unreserved(UnitTypeId::Reaper, false)
                     {
                         UID::Reaper
                     } else {
@@ -90,7 +94,7 @@ impl TerranBot {
     }
 
     fn train_army_unit(&mut self, building: &Unit, unit: UnitTypeId) {
-        if self.can_afford(unit, false) {
+        if self.can_afford_unreserved(unit, false) {
             building.train(unit, true);
             self.subtract_resources(unit, true);
         }
@@ -104,23 +108,24 @@ impl TerranBot {
 
     fn combat_ai(&self, unit: &Unit) {
         if let Some(enemy) = self
-            .units
-            .enemy
-            .units
+            .enemy_memory
+            .units()
             .iter()
+            // Cloaked units cannot be attacked until they are detected
+            .filter(|e| !e.is_cloaked() || e.is_revealed())
             .closer(unit.sight_range() * 1.8, unit)
             .closest(unit)
         {
             if !unit.on_cooldown() {
-                unit.attack(Target::Tag(enemy.tag()), false);
+                unit.attack(self.enemy_target(enemy), false);
             } else {
                 let retreat = unit.position() * 2.0 - enemy.position();
                 unit.attack(Target::Pos(retreat), false);
             }
         }
         // No enemy in range, move towards closest enemy structure
-        else if let Some(structure) = self.units.enemy.structures.iter().closest(unit) {
-            unit.move_to(Target::Tag(structure.tag()), false);
+        else if let Some(structure) = self.enemy_memory.structures().iter().closest(unit) {
+            unit.move_to(self.enemy_target(structure), false);
         }
         // No enemy unit at all, move to enemy start location
         else {
@@ -166,16 +171,19 @@ impl TerranBot {
             })
             .into();
 
-        // If we have more than 15 marines, attack. Otherwise, defend.
-        if self.counter().count(UnitTypeId::Marine)
+        // If we have more than 15 marines, attack. Otherwise, defend. Against a rush, defend
+        // until it has been held.
+        let holding = self.is_enemy_rushing() && self.time < RUSH_DEFENSE_TIME;
+        if (self.counter().count(UnitTypeId::Marine)
             >= self.counter().count(UnitTypeId::Barracks) * 15
+            && !holding)
             || self.supply_used >= 190
         {
             for unit in army {
                 self.marine_ai(unit);
             }
         } else {
-            let targets = self.units.enemy.all.closer(30.0, self.start_location);
+            let targets = self.enemy_memory.all().closer(30.0, self.start_location);
             if targets.is_empty() {
                 for unit in army {
                     if unit.distance_squared(main_ramp) > 7.0_f32.powi(2) {
@@ -185,11 +193,10 @@ impl TerranBot {
             } else {
                 for unit in combat_units {
                     unit.attack(
-                        Target::Tag(
+                        self.enemy_target(
                             targets
                                 .closest(unit)
-                                .expect("We know `targets` isn't empty")
-                                .tag(),
+                                .expect("We know `targets` isn't empty"),
                         ),
                         false,
                     );
@@ -210,26 +217,23 @@ impl TerranBot {
     }
 
     fn move_active_army(&self) {
+        let enemies = self.enemy_memory.units();
         for unit in self.units.my.units.iter().of_types(&COMBAT_UNITS) {
             // Retreat units who are attacked and under 20% HP
             if unit.is_attacked() && unit.health_percentage().is_some_and(|h| h < 0.6) {
-                if let Some(closest_enemy) = self.units.enemy.units.iter().closest(unit.position())
-                {
+                if let Some(closest_enemy) = enemies.iter().closest(unit.position()) {
                     let retreat = unit.position() * 2.0 - closest_enemy.position();
                     unit.move_to(Target::Pos(retreat), false);
                 }
             }
             // Have retreated units close to a battle over 80% HP rejoin the fight
             else if !unit.is_attacking() && unit.health_percentage().is_some_and(|h| h >= 0.95) {
-                if let Some(close_enemy) = self
-                    .units
-                    .enemy
-                    .units
+                if let Some(close_enemy) = enemies
                     .iter()
                     .closer(50.0, unit.position())
                     .closest(unit.position())
                 {
-                    unit.attack(Target::Tag(close_enemy.tag()), false);
+                    unit.attack(self.enemy_target(close_enemy), false);
                 }
             }
         }
@@ -1,82 +1,66 @@
-use crate::bot::{BuildError, TerranBot};
+use crate::{
+    bot::{BuildError, Tag, TerranBot},
+    build_order::{producer_of, BuildStep, ExpansionStyle, Trigger},
+    reservation::Reserver,
+    unit_data::type_aliases,
+};
 use rust_sc2::prelude::*;
 use rustc_hash::FxHashMap;
 use UnitTypeId as UID;
 
 pub(crate) const END_OF_BUILD_PRIO: f32 = 300.0;
-const BUILD_PRIO: &[UID] = &[
-    UID::SupplyDepot,
-    UID::Barracks,
-    UID::Refinery,
-    UID::Refinery,
-    UID::Reaper,
-    UID::OrbitalCommand,
-    UID::SupplyDepot,
-    UID::Factory,
-    UID::Reaper,
-    UID::CommandCenter,
-    UID::Hellion,
-    UID::SupplyDepot,
-    UID::Reaper,
-    UID::Starport,
-    UID::Hellion,
-    UID::BarracksReactor,
-    UID::Refinery,
-    UID::FactoryTechLab,
-    UID::StarportTechLab,
-    UID::OrbitalCommand,
-    UID::Cyclone,
-    UID::Marine,
-    UID::Marine,
-    UID::Raven,
-    UID::SupplyDepot,
-    UID::Marine,
-    UID::Marine,
-    UID::SiegeTank,
-    UID::SupplyDepot,
-    UID::Marine,
-    UID::Marine,
-    UID::Raven,
-    UID::Marine,
-    UID::Marine,
-    UID::SiegeTank,
-    UID::Marine,
-    UID::Marine,
-];
-
-const UPGRADE_PRIO: &[UpgradeId] = &[
-    UpgradeId::ShieldWall,
-    UpgradeId::Stimpack,
-    UpgradeId::TerranInfantryWeaponsLevel1,
-    UpgradeId::TerranInfantryArmorsLevel1,
-    UpgradeId::TerranInfantryWeaponsLevel2,
-    UpgradeId::TerranInfantryArmorsLevel2,
-    UpgradeId::TerranInfantryWeaponsLevel3,
-    UpgradeId::TerranInfantryArmorsLevel3,
-];
+/// Random placements tried when the first placement found cannot be used
+const PLACEMENT_RETRIES: usize = 5;
+/// Estimated income per harvester, until the actual income has been measured
+pub(crate) const MINERAL_INCOME_PER_WORKER: f32 = 0.666;
+pub(crate) const GAS_INCOME_PER_WORKER: f32 = 0.633;
 
 impl TerranBot {
     pub(crate) fn process_base(&mut self, iteration: usize) {
         if iteration % 5 == 0 {
+            for e in self.check_pending_builds() {
+                println!("{:?}", e);
+            }
             self.build_next_in_build_order()
                 .inspect_err(|e| println!("{:?}", e))
                 .unwrap_or_default();
-            self.research_next_in_upgrade_order().unwrap_or_default();
+            self.research_upgrades().unwrap_or_default();
             self.process_supply();
             self.process_structure_abilities();
+            self.process_detection();
+            self.process_orbitals();
+            self.repair_damaged();
+            self.relocate_townhalls();
         }
         if iteration % 5 == 1 {
             self.train_workers();
         }
-        self.move_workers();
+        self.defend_workers();
+        // Gas first, so workers sent to gas are not given a mineral patch in the same step
+        let gas_workers = self.move_workers();
+        self.assign_mineral_workers(&gas_workers);
     }
 
     fn build_next_in_build_order(&mut self) -> Result<(), BuildError> {
-        let next = self
-            .get_current_build_prio()
-            .ok_or(BuildError::EndOfBuildOrder)?;
-        if !self.can_afford(next, next.is_unit()) {
-            if next.is_structure() {
+        self.release(Reserver::NextStep);
+        let step = self.get_current_build_step();
+        self.release_stale_reservations(step.map(|s| s.unit));
+        let step = step.ok_or(BuildError::EndOfBuildOrder)?;
+        let next = step.unit;
+        if step.trigger.is_some_and(|t| !self.trigger_holds(t)) {
+            return Err(BuildError::WaitingForTrigger(next));
+        } else if TECH_REQUIREMENTS
+            .get(&next)
+            .is_some_and(|&requirement| self.counter().tech().count(requirement) == 0)
+        {
+            return Err(BuildError::UnfulfilledTechRequirement(next));
+        } else if producer_of(next).is_some_and(|producer| {
+            producer != UID::SCV && self.counter().tech().count(producer) == 0
+        }) {
+            // Checked before reserving, so a step that cannot start does not hold resources
+            return Err(BuildError::NoProducer(next));
+        } else if !self.can_afford(next, next.is_unit()) {
+            if next.is_structure() && self.reserved_builder(next).is_none() {
                 // If we can afford to build structure in a while, move worker to building spot in preparation
                 let future_location = self
                     .find_suitable_location(next)
@@ -87,55 +71,62 @@ impl TerranBot {
                 let secs_to_location = worker.distance(future_location) / worker.real_speed();
                 if self.can_afford_in(next, secs_to_location) {
                     worker.move_to(Target::Pos(future_location), false);
+                    // The walking builder holds the resources instead of the step
+                    let worker = worker.tag();
+                    self.reserve(Reserver::Builder(worker), next);
+                    return Err(BuildError::CannotAfford(next));
                 }
             }
+            if self.reserved_builder(next).is_none() {
+                self.reserve(Reserver::NextStep, next);
+            }
             return Err(BuildError::CannotAfford(next));
-        } else if TECH_REQUIREMENTS
-            .get(&next)
-            .is_some_and(|&requirement| self.counter().tech().count(requirement) == 0)
-        {
-            return Err(BuildError::UnfulfilledTechRequirement(next));
         }
 
-        match next {
+        let (builder, location) = match next {
             UID::CommandCenter => self.build_expansion()?,
-            UID::OrbitalCommand => self.upgrade_townhall()?,
+            UID::OrbitalCommand | UID::PlanetaryFortress => self.upgrade_townhall(next)?,
             _ if next == self.race_values.gas => self.build_gas_building()?,
             unit if next.is_unit() => self.train_unit(unit)?,
             addon if next.is_addon() => self.build_addon(addon)?,
             structure if next.is_structure() => self.build_structure(structure)?,
-            _ => {}
-        }
+            _ => return Err(BuildError::InvalidArgument(next)),
+        };
 
+        // "construction started" is logged once the build is confirmed by `check_pending_builds`
+        self.track_build(next, builder, location);
+        self.release(Reserver::Builder(builder));
         self.subtract_resources(next, next.is_unit());
 
-        let time = format!(
-            "{:0>2}:{:0>2} ",
-            self.time as usize / 60,
-            self.time as usize % 60
-        );
-        self.log(&format!(
-            "{}{:?}: construction started (m: {}, g: {} {}/{})",
-            time, next, self.minerals, self.vespene, self.supply_used, self.supply_cap
-        ));
-
         Ok(())
     }
 
-    fn build_expansion(&self) -> Result<(), BuildError> {
-        // Find closest expansion site
-        let expansion = self.get_expansion().ok_or(BuildError::NoSuitableLocation(
-            self.race_values.start_townhall,
-        ))?;
+    /// Builds a Command Center at the best free expansion, or in the main if the build order
+    /// flies it there, returning the builder's tag and the location
+    fn build_expansion(&self) -> Result<(Tag, Option<Point2>), BuildError> {
+        // Find the best expansion site, or a spot in the main
+        let expansion = self
+            .find_suitable_location(self.race_values.start_townhall)
+            .ok_or(BuildError::NoSuitableLocation(
+                self.race_values.start_townhall,
+            ))?;
         // Find worker closest to expansion site
         let builder = self
-            .get_closest_free_worker(expansion.loc)
+            .reserved_builder(self.race_values.start_townhall)
+            .or_else(|| self.get_closest_free_worker(expansion))
             .ok_or(BuildError::NoSuitableWorker)?;
-        builder.build(self.race_values.start_townhall, expansion.loc, false);
-        Ok(())
+        builder.build(self.race_values.start_townhall, expansion, false);
+        Ok((builder.tag(), Some(expansion)))
     }
 
-    fn upgrade_townhall(&self) -> Result<(), BuildError> {
+    /// Morphs the idle Command Center closest to the main into an Orbital Command or a
+    /// Planetary Fortress
+    fn upgrade_townhall(&self, townhall: UID) -> Result<(Tag, Option<Point2>), BuildError> {
+        let ability = match townhall {
+            UID::OrbitalCommand => AbilityId::UpgradeToOrbitalOrbitalCommand,
+            UID::PlanetaryFortress => AbilityId::UpgradeToPlanetaryFortressPlanetaryFortress,
+            _ => return Err(BuildError::InvalidArgument(townhall)),
+        };
         let command_center = self
             .units
             .my
@@ -144,12 +135,12 @@ impl TerranBot {
             .of_type(UID::CommandCenter)
             .idle()
             .closest(self.start_location)
-            .ok_or(BuildError::NoProducer(UID::OrbitalCommand))?;
-        command_center.use_ability(AbilityId::UpgradeToOrbitalOrbitalCommand, false);
-        Ok(())
+            .ok_or(BuildError::NoProducer(townhall))?;
+        command_center.use_ability(ability, false);
+        Ok((command_center.tag(), None))
     }
 
-    fn build_gas_building(&self) -> Result<(), BuildError> {
+    fn build_gas_building(&self) -> Result<(Tag, Option<Point2>), BuildError> {
         let geyser = self
             .units
             .my
@@ -159,14 +150,15 @@ impl TerranBot {
             .ok_or(BuildError::NoSuitableLocation(self.race_values.gas))?;
 
         let builder = self
-            .get_closest_free_worker(geyser.position())
+            .reserved_builder(self.race_values.gas)
+            .or_else(|| self.get_closest_free_worker(geyser.position()))
             .ok_or(BuildError::NoSuitableWorker)?;
 
         builder.build_gas(geyser.tag(), false);
-        Ok(())
+        Ok((builder.tag(), Some(geyser.position())))
     }
 
-    fn train_unit(&self, unit: UID) -> Result<(), BuildError> {
+    fn train_unit(&self, unit: UID) -> Result<(Tag, Option<Point2>), BuildError> {
         let producer = *PRODUCERS.get(&unit).ok_or(BuildError::NoProducer(unit))?;
         let producer = self
             .units
@@ -178,29 +170,44 @@ impl TerranBot {
             .closest(self.start_location)
             .ok_or(BuildError::NoProducer(unit))?;
         producer.train(unit, true);
-        Ok(())
+        Ok((producer.tag(), None))
     }
 
-    fn build_structure(&self, structure: UID) -> Result<(), BuildError> {
+    pub(crate) fn build_structure(
+        &self,
+        structure: UID,
+    ) -> Result<(Tag, Option<Point2>), BuildError> {
         let location = self
             .find_suitable_location(structure)
             .ok_or(BuildError::NoSuitableLocation(structure))?;
         let builder = self
-            .get_closest_free_worker(location)
+            .reserved_builder(structure)
+            .or_else(|| self.get_closest_free_worker(location))
             .ok_or(BuildError::NoSuitableWorker)?;
 
         builder.build(structure, location, false);
 
-        Ok(())
+        Ok((builder.tag(), Some(location)))
     }
 
     fn find_suitable_location(&self, structure: UID) -> Option<Point2> {
+        if structure == self.race_values.start_townhall
+            && self.build_order().expand == ExpansionStyle::InPlace
+        {
+            return self.best_expansion();
+        }
+        if let Some(location) = self
+            .find_wall_location(structure)
+            .or_else(|| self.find_layout_location(structure))
+        {
+            return Some(location);
+        }
         let main_base = if structure == self.race_values.supply {
             self.start_location.towards(self.game_info.map_center, 2.0)
         } else {
             self.start_location.towards(self.game_info.map_center, 10.0)
         };
-        let placement_options = PlacementOptions {
+        let placement_options = |random| PlacementOptions {
             step: if structure == self.race_values.supply {
                 3
             } else {
@@ -208,12 +215,25 @@ impl TerranBot {
             },
             addon: matches!(structure, UID::Barracks | UID::Factory | UID::Starport),
             max_distance: 30,
+            random,
             ..Default::default()
         };
-        self.find_placement(structure, main_base, placement_options)
+        let is_usable = |location: Point2| {
+            !self.is_failed_location(location) && !self.is_excluded(structure, location)
+        };
+        let location = self.find_placement(structure, main_base, placement_options(false))?;
+        if is_usable(location) {
+            Some(location)
+        } else {
+            // Building there failed before or would be in the way, so look for another spot
+            (0..PLACEMENT_RETRIES).find_map(|_| {
+                self.find_placement(structure, main_base, placement_options(true))
+                    .filter(|&location| is_usable(location))
+            })
+        }
     }
 
-    fn build_addon(&mut self, addon: UID) -> Result<(), BuildError> {
+    pub(crate) fn build_addon(&mut self, addon: UID) -> Result<(Tag, Option<Point2>), BuildError> {
         let producer = match addon {
             UID::BarracksReactor | UID::BarracksTechLab => UID::Barracks,
             UID::FactoryReactor | UID::FactoryTechLab => UID::Factory,
@@ -234,7 +254,7 @@ impl TerranBot {
 
         producer.train(addon, true);
 
-        Ok(())
+        Ok((producer.tag(), None))
     }
 
     fn process_supply(&mut self) {
@@ -243,116 +263,60 @@ impl TerranBot {
         let ordered = self.counter().ordered().count(structure);
         if self.time > END_OF_BUILD_PRIO
             && ((self.supply_left < 5 && ordered == 0) || (self.supply_left < 2 && ordered == 1))
-            && self.can_afford(structure, false)
+            && self.can_afford_unreserved(structure, false)
         {
-            self.build_structure(structure).unwrap_or_default();
-            self.subtract_resources(structure, false);
+            if let Ok((builder, location)) = self.build_structure(structure) {
+                self.track_build(structure, builder, location);
+                self.subtract_resources(structure, false);
+            }
         }
     }
 
     fn process_structure_abilities(&self) {
-        // Lower supply
+        // Lower supply, and raise it again to close the wall when enemies come closest. Lowered
+        // depots have their own type, so both have to be looked at.
         for supply in self
             .units
             .my
             .structures
             .iter()
-            .of_type(self.race_values.supply)
+            .of_types(&vec![UID::SupplyDepot, UID::SupplyDepotLowered])
+            .ready()
         {
             if let Some(unit) = self
                 .units
                 .all
                 .iter()
-                .filter(|u| u.type_id().is_unit())
+                .filter(|u| u.type_id().is_unit() && !u.is_flying())
                 .closest(supply)
             {
                 if unit.is_mine() || unit.is_ally() {
-                    supply.use_ability(AbilityId::MorphSupplyDepotLower, false);
-                } else {
+                    if supply.type_id() == UID::SupplyDepot {
+                        supply.use_ability(AbilityId::MorphSupplyDepotLower, false);
+                    }
+                } else if supply.type_id() == UID::SupplyDepotLowered {
                     supply.use_ability(AbilityId::MorphSupplyDepotRaise, false);
                 }
             }
         }
-
-        // Call down MULEs
-        for orbital in self.units.my.townhalls.iter().of_type(UID::OrbitalCommand) {
-            if !orbital.has_ability(AbilityId::CalldownMULECalldownMULE) {
-                continue;
-            }
-            if let Some(townhall) = self
-                .units
-                .my
-                .townhalls
-                .iter()
-                .filter(|t| t.assigned_harvesters() < t.ideal_harvesters())
-                .closest(orbital.position())
-            {
-                if let Some(mineral) = self.units.mineral_fields.closest(townhall.position()) {
-                    orbital.command(
-                        AbilityId::CalldownMULECalldownMULE,
-                        Target::Tag(mineral.tag()),
-                        false,
-                    );
-                }
-            }
-        }
-    }
-
-    fn research_next_in_upgrade_order(&mut self) -> Result<(), BuildError> {
-        let upgrade = self
-            .get_current_research_prio()
-            .ok_or(BuildError::EndOfBuildOrder)?;
-        if !self.can_afford_upgrade(upgrade) {
-            return Err(BuildError::CannotAffordUpgrade(upgrade));
-        } else if matches!(
-            upgrade,
-            UpgradeId::TerranInfantryWeaponsLevel2
-                | UpgradeId::TerranInfantryArmorsLevel2
-                | UpgradeId::TerranInfantryWeaponsLevel3
-                | UpgradeId::TerranInfantryArmorsLevel3
-        ) && self.counter().count(UID::Armory) == 0
-        {
-            return Err(BuildError::UnfulfilledTechRequirement(UID::Armory));
-        }
-
-        let producer = *RESEARCHERS
-            .get(&upgrade)
-            .ok_or(BuildError::NoResearcher(upgrade))?;
-        let producer = self
-            .units
-            .my
-            .structures
-            .iter()
-            .of_type(producer)
-            .idle()
-            .ready()
-            .closest(self.start_location)
-            .ok_or(BuildError::NoResearcher(upgrade))?;
-        producer.research(upgrade, false);
-        self.subtract_upgrade_cost(upgrade);
-        self.upgrade_prio_index += 1;
-
-        let time = format!(
-            "{:0>2}:{:0>2}",
-            self.time as usize / 60,
-            self.time as usize % 60
-        );
-        self.log(&format!("{} {:?}: research started", time, upgrade));
-        Ok(())
     }
 
-    /// Returns the last item in the build priority list for which all previous items are built
-    pub(crate) fn get_current_build_prio(&self) -> Option<UID> {
-        let mut unit_to_build = None;
-        for (i, unit) in BUILD_PRIO.iter().chain(&[UID::NotAUnit]).enumerate() {
+    /// Returns the last step in the build order for which all previous steps are built
+    pub(crate) fn get_current_build_step(&self) -> Option<BuildStep> {
+        let mut step_to_build = None;
+        let steps = self
+            .build_order()
+            .active_steps(|condition| self.condition_holds(condition));
+        for (i, step) in steps.iter().map(Some).chain([None]).enumerate() {
             // Create hashmap of each unit and their count before current unit in build prio
             let prerequisites = ([&self.race_values.start_townhall])
                 .into_iter()
-                .chain(BUILD_PRIO.iter().take(i))
+                .chain(steps.iter().take(i).map(|s| &s.unit))
                 .fold(FxHashMap::default(), |mut acc, u| {
                     let unit = match u {
                         UID::OrbitalCommand
                         | UID::OrbitalCommandFlying
+                        | UID::PlanetaryFortress
                         | UID::CommandCenterFlying => &UID::CommandCenter,
                         _ => u,
                     };
@@ -374,18 +338,41 @@ impl TerranBot {
                 break;
             }
             println!();
-            if *unit == UID::NotAUnit {
-                // Return None if all items are built
-                unit_to_build = None;
-            } else {
-                unit_to_build = Some(unit);
-            }
+            // Becomes None if all steps are built
+            step_to_build = step.copied();
         }
-        unit_to_build.copied()
+        step_to_build
     }
 
-    pub(crate) fn get_current_research_prio(&self) -> Option<UpgradeId> {
-        UPGRADE_PRIO.get(self.upgrade_prio_index).copied()
+    /// Returns the unit of the current build order step
+    pub(crate) fn get_current_build_prio(&self) -> Option<UID> {
+        self.get_current_build_step().map(|s| s.unit)
+    }
+
+    /// Returns true if the step trigger is fulfilled
+    pub(crate) fn trigger_holds(&self, trigger: Trigger) -> bool {
+        match trigger {
+            Trigger::Supply(supply) => self.supply_used >= supply,
+            Trigger::Time(time) => self.time >= time,
+            Trigger::Progress(structure, progress) => self
+                .units
+                .my
+                .structures
+                .iter()
+                .of_types(&type_aliases(structure))
+                .any(|s| s.build_progress() >= progress),
+        }
+    }
+
+    /// Switches to the build and upgrade order for the given enemy race.
+    ///
+    /// Steps of the new build order which our existing units already fulfill are skipped by
+    /// `get_current_build_step`, and upgrades which are already researched or in progress are
+    /// skipped by `research_upgrades`, so switching does not duplicate anything.
+    pub(crate) fn switch_matchup(&mut self, race: Race) {
+        self.matchup = Some(race);
+        let msg = format!("Playing build order \"{}\"", self.build_order().name);
+        self.log(&msg);
     }
 
     fn get_closest_free_worker(&self, location: Point2) -> Option<&Unit> {
@@ -408,17 +395,10 @@ impl TerranBot {
         )
     }
 
-    fn move_workers(&self) {
-        // To keep track of idle workers
-        let mut idle_workers: Vec<_> = self
-            .units
-            .my
-            .workers
-            .iter()
-            .idle()
-            .map(|w| w.tag())
-            .collect();
-
+    /// Keeps gas buildings saturated, returning the workers sent to gas. Workers taken off gas
+    /// are given a mineral patch by `assign_mineral_workers`.
+    fn move_workers(&self) -> Vec<Tag> {
+        let mut gas_workers = Vec::new();
         // Fill all gas buildings with workers
         for gas_building in self
             .units
@@ -434,6 +414,10 @@ impl TerranBot {
                 .iter()
                 .filter(|w| {
                     !w.is_constructing()
+                        && !self.defending_workers.contains_key(&w.tag())
+                        && !self.is_scout(w.tag())
+                        && !self.repairers.contains_key(&w.tag())
+                        && !self.is_reserved_builder(w.tag())
                         && !w
                             .target_tag()
                             .is_some_and(|tag| self.units.my.gas_buildings.get(tag).is_some())
@@ -446,34 +430,10 @@ impl TerranBot {
                 continue;
             };
             worker.gather(gas_building.tag(), false);
-            if let Some(idx) = idle_workers.iter().position(|&t| t == worker.tag()) {
-                idle_workers.swap_remove(idx);
-            }
+            gas_workers.push(worker.tag());
         }
 
-        // For each resource gather point with too many workers, make unnecessary workers idle
-        for townhall in self
-            .units
-            .my
-            .townhalls
-            .iter()
-            .filter(|t| t.assigned_harvesters() > t.ideal_harvesters())
-        {
-            if let Some(worker) = self
-                .units
-                .my
-                .workers
-                .iter()
-                .filter(|w| {
-                    w.target_tag()
-                        .is_some_and(|tag| self.units.mineral_fields.get(tag).is_some())
-                })
-                .closest(townhall)
-            {
-                worker.stop(false);
-                idle_workers.push(worker.tag());
-            }
-        }
+        // For each gas building with too many workers, make unnecessary workers idle
         for gas_building in self
             .units
             .my
@@ -490,48 +450,13 @@ impl TerranBot {
                 .closest(gas_building.position())
             {
                 worker.stop(false);
-                idle_workers.push(worker.tag());
-            }
-        }
-
-        // Let each townhall with too few workers yoink some idle ones
-        for townhall in &self.units.my.townhalls {
-            let wanted_amount = if let (Some(ideal), Some(assigned)) =
-                (townhall.ideal_harvesters(), townhall.assigned_harvesters())
-            {
-                ideal.saturating_sub(assigned)
-            } else {
-                eprintln!("Error: tried to get assigned_harvesters from non-townhall unit");
-                continue;
-            };
-            for _ in 0..wanted_amount {
-                if let Some(worker) = self
-                    .units
-                    .my
-                    .workers
-                    .find_tags(&idle_workers)
-                    .closest(townhall)
-                {
-                    let resource = self
-                    .units
-                    .mineral_fields
-                    .iter()
-                    .closer(8.0, townhall)
-                    .closest(worker)
-                    .expect(
-                        "If ideal_harvesters > 0 then townhall should have nearby mineral resource",
-                    );
-                    worker.gather(resource.tag(), false);
-                    if let Some(idx) = idle_workers.iter().position(|&t| t == worker.tag()) {
-                        idle_workers.swap_remove(idx);
-                    }
-                }
             }
         }
+        gas_workers
     }
 
     fn train_workers(&mut self) {
-        if !self.can_afford(self.race_values.worker, false)
+        if !self.can_afford_unreserved(self.race_values.worker, false)
             || (self
                 .get_current_build_prio()
                 .is_some_and(|b| b == UID::OrbitalCommand)
@@ -558,12 +483,12 @@ impl TerranBot {
             .townhalls
             .iter()
             .almost_idle()
-            .filter(|t| t.is_ready())
+            .filter(|t| t.is_ready() && !self.is_in_base_townhall(t))
             .take(target_amount.saturating_sub(current_amount as usize))
             .cloned()
             .collect();
         for townhall in townhalls {
-            if !self.can_afford(worker, false) {
+            if !self.can_afford_unreserved(worker, false) {
                 // We cannot afford any more workers anyway
                 break;
             }
@@ -577,32 +502,4 @@ impl TerranBot {
         self.minerals + (self.mineral_income() * secs) as u32 > cost.minerals
             && self.vespene + (self.gas_income() * secs) as u32 > cost.vespene
     }
-
-    fn mineral_income(&self) -> f32 {
-        const MINERAL_INCOME_PER_WORKER: f32 = 0.666;
-        self.units
-            .my
-            .workers
-            .iter()
-            .filter(|w| {
-                w.target_tag()
-                    .is_some_and(|t| self.units.mineral_fields.contains_tag(t))
-            })
-            .count() as f32
-            * MINERAL_INCOME_PER_WORKER
-    }
-
-    fn gas_income(&self) -> f32 {
-        const GAS_INCOME_PER_WORKER: f32 = 0.633;
-        self.units
-            .my
-            .workers
-            .iter()
-            .filter(|w| {
-                w.target_tag()
-                    .is_some_and(|t| self.units.my.gas_buildings.contains_tag(t))
-            })
-            .count() as f32
-            * GAS_INCOME_PER_WORKER
-    }
 }
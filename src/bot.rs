@@ -1,4 +1,20 @@
+use crate::{
+    build_order::{BuildOrder, Matchups},
+    construction::PendingBuild,
+    enemy_memory::EnemyMemory,
+    income::IncomeSample,
+    layout::BaseLayout,
+    research::PendingResearch,
+    reservation::Reservation,
+    scan::Scanner,
+    scouting::Scouting,
+    strategy::StrategyGuess,
+    wall::RampWall,
+    worker_defense::DefenseRole,
+};
 use rust_sc2::prelude::*;
+use rustc_hash::FxHashMap;
+use std::collections::VecDeque;
 
 #[bot]
 #[derive(Default)]
@@ -15,21 +31,78 @@ impl Player for WorkerRush {
     }
 }
 
-type Tag = u64;
+pub(crate) type Tag = u64;
 
 #[bot]
 #[derive(Default)]
 pub(crate) struct TerranBot {
-    pub(crate) upgrade_prio_index: usize,
+    pub(crate) build_orders: Matchups,
+    /// The enemy race we play the build order for, `None` before the game has started
+    pub(crate) matchup: Option<Race>,
+    /// Research commands which have not yet shown up in the researcher's orders
+    pub(crate) pending_research: Vec<PendingResearch>,
+    /// Build commands which have not yet been seen to start
+    pub(crate) pending_builds: Vec<PendingBuild>,
+    /// Locations where a structure could not be placed, and when it failed
+    pub(crate) failed_locations: Vec<(Point2, f32)>,
+    /// Minerals and gas held back for the build order
+    pub(crate) reservations: Vec<Reservation>,
+    /// Resources gathered in total over the last seconds, oldest first
+    pub(crate) income_samples: VecDeque<IncomeSample>,
+    /// The mineral patch each mining worker is bound to
+    pub(crate) mining_assignments: FxHashMap<Tag, Tag>,
+    /// Workers pulled off mining to fight or flee enemies in a mineral line
+    pub(crate) defending_workers: FxHashMap<Tag, DefenseRole>,
+    /// The target each repairing SCV is assigned to
+    pub(crate) repairers: FxHashMap<Tag, Tag>,
+    /// Where the main ramp is walled off, `None` before the game has started
+    pub(crate) ramp_wall: Option<RampWall>,
+    /// Where structures are placed, planned when the game starts
+    pub(crate) layout: BaseLayout,
+    /// Ground distance from the main to every expansion, measured when the game starts
+    pub(crate) expansion_distances: Vec<(Point2, f32)>,
+    /// Where each lifted townhall is flown to and landed
+    pub(crate) townhall_landings: FxHashMap<Tag, Point2>,
+    /// Type and time of first sighting of every enemy unit we have seen
+    pub(crate) enemies_seen: FxHashMap<Tag, (UnitTypeId, f32)>,
+    /// Enemy units as they were last seen, kept after they leave vision
+    pub(crate) enemy_memory: EnemyMemory,
+    /// The most confident guess of the enemy opening, `None` until something points to one
+    pub(crate) enemy_strategy: Option<StrategyGuess>,
+    /// The SCV scouting the enemy and the way it takes
+    pub(crate) scouting: Scouting,
+    /// Where Orbital Commands have scanned
+    pub(crate) scanner: Scanner,
+    /// When cloaked or burrowed enemies were first noticed, `None` before
+    pub(crate) cloak_noticed_at: Option<f32>,
 }
 
 impl TerranBot {
+    /// Creates the bot, remembering enemies out of vision for `memory_decay` seconds
+    pub(crate) fn new(build_orders: Matchups, memory_decay: f32) -> Self {
+        Self {
+            build_orders,
+            enemy_memory: EnemyMemory::new(memory_decay),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the build order for the current matchup
+    pub(crate) fn build_order(&self) -> &BuildOrder {
+        self.build_orders.get(self.matchup.unwrap_or(Race::Random))
+    }
+
     pub(crate) fn log(&mut self, msg: &str) {
         self.chat_ally(msg);
         println!("{}", msg);
     }
 }
 
+/// Formats game seconds as `mm:ss`
+pub(crate) fn format_time(secs: f32) -> String {
+    format!("{:0>2}:{:0>2}", secs as usize / 60, secs as usize % 60)
+}
+
 impl Player for TerranBot {
     fn get_player_settings(&self) -> PlayerSettings {
         PlayerSettings {
@@ -39,6 +112,11 @@ impl Player for TerranBot {
     }
 
     fn on_start(&mut self) -> SC2Result<()> {
+        let enemy_race = self.enemy_race;
+        self.switch_matchup(enemy_race);
+        self.ramp_wall = self.plan_ramp_wall();
+        self.layout = self.plan_layout();
+        self.expansion_distances = self.measure_expansion_distances();
         for worker in &self.units.my.workers {
             worker.stop(false);
         }
@@ -53,6 +131,10 @@ impl Player for TerranBot {
     }
 
     fn on_step(&mut self, iteration: usize) -> SC2Result<()> {
+        self.record_income();
+        self.record_seen_enemies();
+        self.remember_enemies();
+        self.classify_enemy_strategy();
         self.process_base(iteration);
         self.process_army(iteration);
         Ok(())
@@ -63,11 +145,10 @@ impl Player for TerranBot {
     }
 
     fn on_event(&mut self, _event: Event) -> SC2Result<()> {
-        let time = format!(
-            "{:0>2}:{:0>2} ",
-            self.time as usize / 60,
-            self.time as usize % 60
-        );
+        let time = format!("{} ", format_time(self.time));
+        if let Event::UnitDestroyed(tag, _) = _event {
+            self.enemy_memory.forget(tag);
+        }
         match _event {
             Event::UnitDestroyed(tag, Some(alliance)) => {
                 if let Some(unit) = self.units.all.get(tag) {
@@ -96,6 +177,9 @@ impl Player for TerranBot {
             //     }
             // }
             Event::ConstructionComplete(tag) => {
+                if self.units.my.townhalls.contains_tag(tag) {
+                    self.transfer_workers(tag);
+                }
                 if let Some(unit) = self.units.all.get(tag).cloned() {
                     let count = self.counter().alias().all().count(unit.type_id());
                     // print!("{}", time);
@@ -111,6 +195,7 @@ impl Player for TerranBot {
             Event::RandomRaceDetected(race) => {
                 print!("{}", time);
                 println!("Detected random opponent to be {:?}", race);
+                self.switch_matchup(race);
             }
             _ => {}
         }
@@ -122,9 +207,18 @@ impl Player for TerranBot {
 pub(crate) enum BuildError {
     NoSuitableLocation(UnitTypeId),
     CannotAfford(UnitTypeId),
+    SupplyBlocked(UnitTypeId),
     NoSuitableWorker,
+    /// The builder or producer died before the build started
+    BuilderLost(UnitTypeId),
+    /// The builder or producer dropped the command, at the location if it was a structure
+    CommandDropped(UnitTypeId, Option<Point2>),
+    /// The builder did not start building in time
+    BuildTimedOut(UnitTypeId),
     UnfulfilledTechRequirement(UnitTypeId),
+    UnfulfilledUpgradeRequirement(UpgradeId),
     EndOfBuildOrder,
+    WaitingForTrigger(UnitTypeId),
     NoProducer(UnitTypeId),
     InvalidArgument(UnitTypeId),
     CannotAffordUpgrade(UpgradeId),
@@ -0,0 +1,689 @@
+use crate::{scan::DEFAULT_SCAN_RESERVE, strategy::EnemyStrategy, unit_data::upgrade_cost};
+use rust_sc2::prelude::*;
+use serde::{
+    de::{value::StrDeserializer, Error as _, IgnoredAny, IntoDeserializer},
+    Deserialize, Deserializer,
+};
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+use UnitTypeId as UID;
+
+/// A build order loaded from a data file. The active steps are walked in order by
+/// `get_current_build_prio`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct BuildOrder {
+    pub(crate) name: String,
+    pub(crate) entries: Vec<BuildEntry>,
+    /// Upgrades to research, in order of priority
+    pub(crate) upgrades: Vec<UpgradeId>,
+    pub(crate) expand: ExpansionStyle,
+    /// When an SCV is sent to scout the enemy, `None` to not scout
+    pub(crate) scout: Option<Trigger>,
+    /// Scanner Sweeps kept in reserve instead of calling down MULEs
+    pub(crate) scan_reserve: u32,
+    /// Game times in seconds to scan the enemy main for tech
+    pub(crate) tech_scans: Vec<f32>,
+}
+
+/// Where Command Centers for new bases are built
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ExpansionStyle {
+    /// At the expansion itself
+    #[default]
+    InPlace,
+    /// Inside the main, flown to the expansion once the way is clear
+    InBase,
+    /// Inside the main and morphed to an Orbital Command before it is flown to the expansion
+    InBaseOrbital,
+}
+
+/// The build order to play against each enemy race
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Matchups {
+    pub(crate) tvt: BuildOrder,
+    pub(crate) tvp: BuildOrder,
+    pub(crate) tvz: BuildOrder,
+    pub(crate) tvr: BuildOrder,
+}
+
+impl Matchups {
+    /// Plays the same build order in every matchup
+    pub(crate) fn same(build_order: BuildOrder) -> Self {
+        Self {
+            tvt: build_order.clone(),
+            tvp: build_order.clone(),
+            tvz: build_order.clone(),
+            tvr: build_order,
+        }
+    }
+
+    pub(crate) fn get(&self, race: Race) -> &BuildOrder {
+        match race {
+            Race::Terran => &self.tvt,
+            Race::Protoss => &self.tvp,
+            Race::Zerg => &self.tvz,
+            // Random, or not yet known
+            _ => &self.tvr,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum BuildEntry {
+    Step(BuildStep),
+    Branch(Branch),
+}
+
+/// A branch point which inserts different steps depending on what we know about the enemy
+#[derive(Debug, Clone)]
+pub(crate) struct Branch {
+    pub(crate) condition: Condition,
+    pub(crate) then: Vec<BuildEntry>,
+    pub(crate) otherwise: Vec<BuildEntry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Condition {
+    /// At least `count` enemy units of this type have been seen, optionally before a game time
+    EnemySeen {
+        unit: UID,
+        count: usize,
+        before: Option<f32>,
+    },
+    /// The enemy race is known to be this race
+    EnemyRace(Race),
+    /// The enemy is believed to play this opening
+    EnemyStrategy(EnemyStrategy),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BuildStep {
+    pub(crate) unit: UID,
+    /// If set, the step is not started before the trigger holds
+    pub(crate) trigger: Option<Trigger>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Trigger {
+    /// Used supply is at least this amount
+    Supply(u32),
+    /// Game time in seconds has passed
+    Time(f32),
+    /// A structure of this type has reached this build progress (0.0 to 1.0)
+    Progress(UID, f32),
+}
+
+/// The on-disk (TOML) representation of a build order
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BuildOrderFile {
+    name: String,
+    steps: Vec<StepEntry>,
+    #[serde(default)]
+    upgrades: Vec<String>,
+    #[serde(default)]
+    expand: ExpansionStyle,
+    /// When the scout is sent, e.g. `{ when = "SupplyDepot", percent = 0 }`
+    scout: Option<TriggerTable>,
+    #[serde(default)]
+    scans: ScanTable,
+}
+
+/// A step is either a unit name, optionally prefixed with a supply count (`"14 SupplyDepot"`),
+/// a table such as `{ unit = "Factory", time = "2:30" }`, or a branch such as
+/// `{ if = { enemy = "Hatchery", count = 2, before = "1:30" }, then = ["Bunker"] }`
+enum StepEntry {
+    Name(String),
+    Branch(BranchTable),
+    Table(StepTable),
+}
+
+/// Tables with an `if` key are branches, other tables are steps. Deciding this up front, instead
+/// of trying each in turn, keeps the error of a malformed branch or step, e.g. which field is
+/// unknown.
+impl<'de> Deserialize<'de> for StepEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::String(name) => Ok(Self::Name(name)),
+            value @ toml::Value::Table(_) if value.get("if").is_some() => {
+                BranchTable::deserialize(value)
+                    .map(Self::Branch)
+                    .map_err(D::Error::custom)
+            }
+            value @ toml::Value::Table(_) => StepTable::deserialize(value)
+                .map(Self::Table)
+                .map_err(D::Error::custom),
+            value => Err(D::Error::custom(format!(
+                "expected a unit name or a table, found {}",
+                value.type_str()
+            ))),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BranchTable {
+    #[serde(rename = "if")]
+    condition: ConditionTable,
+    then: Vec<StepEntry>,
+    #[serde(rename = "else", default)]
+    otherwise: Vec<StepEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConditionTable {
+    enemy: Option<String>,
+    count: Option<usize>,
+    before: Option<String>,
+    race: Option<String>,
+    strategy: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StepTable {
+    unit: String,
+    #[serde(flatten)]
+    trigger: TriggerTable,
+}
+
+/// The trigger fields of a table. `deny_unknown_fields` does not work with `flatten`, so the
+/// fields left over are collected in `unknown` and rejected by `TriggerTable::parse`.
+#[derive(Deserialize)]
+struct TriggerTable {
+    supply: Option<u32>,
+    time: Option<String>,
+    when: Option<String>,
+    percent: Option<f32>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+impl TriggerTable {
+    fn parse(&self) -> Result<Option<Trigger>, String> {
+        if let Some(field) = self.unknown.keys().next() {
+            return Err(format!("unknown field `{}`", field));
+        }
+        parse_trigger(
+            self.supply,
+            self.time.as_deref(),
+            self.when.as_deref(),
+            self.percent,
+        )
+    }
+}
+
+/// How Orbital Command energy is split between scans and MULEs, e.g.
+/// `{ reserve = 1, tech = ["4:30", "7:00"] }`
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ScanTable {
+    reserve: Option<u32>,
+    #[serde(default)]
+    tech: Vec<String>,
+}
+
+#[derive(Debug)]
+pub(crate) enum BuildOrderError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    UnknownUnit { step: usize, name: String },
+    ImpossibleStep { step: usize, unit: UID },
+    InvalidTrigger { step: usize, reason: String },
+    InvalidCondition { step: usize, reason: String },
+    UnknownUpgrade(String),
+    ImpossibleUpgrade(UpgradeId),
+    UnsupportedUpgrade(UpgradeId),
+    InvalidScoutTrigger(String),
+    InvalidScanTime(String),
+}
+
+impl fmt::Display for BuildOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read file: {}", e),
+            Self::Parse(e) => write!(f, "could not parse file: {}", e),
+            Self::UnknownUnit { step, name } => {
+                write!(f, "step {}: unknown unit type \"{}\"", step, name)
+            }
+            Self::ImpossibleStep { step, unit } => {
+                write!(f, "step {}: {:?} cannot be produced by Terran", step, unit)
+            }
+            Self::InvalidTrigger { step, reason } => {
+                write!(f, "step {}: invalid trigger: {}", step, reason)
+            }
+            Self::InvalidCondition { step, reason } => {
+                write!(f, "step {}: invalid branch condition: {}", step, reason)
+            }
+            Self::UnknownUpgrade(name) => write!(f, "unknown upgrade \"{}\"", name),
+            Self::ImpossibleUpgrade(upgrade) => {
+                write!(f, "{:?} cannot be researched by Terran", upgrade)
+            }
+            Self::UnsupportedUpgrade(upgrade) => {
+                write!(f, "{:?} is not supported, its cost is unknown", upgrade)
+            }
+            Self::InvalidScoutTrigger(reason) => write!(f, "invalid scout trigger: {}", reason),
+            Self::InvalidScanTime(time) => {
+                write!(f, "scan time \"{}\" is not a time (m:ss)", time)
+            }
+        }
+    }
+}
+
+impl BuildOrder {
+    /// Reads and validates a build order from a TOML file
+    pub(crate) fn load(path: impl AsRef<Path>) -> Result<Self, BuildOrderError> {
+        let contents = fs::read_to_string(path).map_err(BuildOrderError::Io)?;
+        Self::parse(&contents)
+    }
+
+    /// Parses and validates a build order from a TOML string
+    pub(crate) fn parse(contents: &str) -> Result<Self, BuildOrderError> {
+        let file: BuildOrderFile = toml::from_str(contents).map_err(BuildOrderError::Parse)?;
+        // Steps are numbered from 1 in error messages, in the order they are read in the file
+        let mut step = 0;
+        Ok(Self {
+            name: file.name,
+            entries: parse_entries(&mut step, &file.steps)?,
+            upgrades: file
+                .upgrades
+                .iter()
+                .map(|name| {
+                    let upgrade = parse_upgrade(name)
+                        .ok_or_else(|| BuildOrderError::UnknownUpgrade(name.clone()))?;
+                    if !RESEARCHERS
+                        .get(&upgrade)
+                        .is_some_and(|&researcher| producer_of(researcher).is_some())
+                    {
+                        return Err(BuildOrderError::ImpossibleUpgrade(upgrade));
+                    } else if upgrade_cost(upgrade).is_none() {
+                        return Err(BuildOrderError::UnsupportedUpgrade(upgrade));
+                    }
+                    Ok(upgrade)
+                })
+                .collect::<Result<_, _>>()?,
+            expand: file.expand,
+            scout: file
+                .scout
+                .as_ref()
+                .map(|table| {
+                    table
+                        .parse()?
+                        .ok_or_else(|| "one of `supply`, `time` and `when` is needed".to_string())
+                })
+                .transpose()
+                .map_err(BuildOrderError::InvalidScoutTrigger)?,
+            scan_reserve: file.scans.reserve.unwrap_or(DEFAULT_SCAN_RESERVE),
+            tech_scans: file
+                .scans
+                .tech
+                .iter()
+                .map(|time| {
+                    parse_time(time).ok_or_else(|| BuildOrderError::InvalidScanTime(time.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                // `process_orbitals` makes the scans in order
+                .map(|mut times| {
+                    times.sort_by(f32::total_cmp);
+                    times
+                })?,
+        })
+    }
+
+    /// Returns the steps to build, taking the branches for which `holds` returns true
+    pub(crate) fn active_steps(&self, holds: impl Fn(&Condition) -> bool) -> Vec<BuildStep> {
+        fn flatten(
+            entries: &[BuildEntry],
+            holds: &impl Fn(&Condition) -> bool,
+            steps: &mut Vec<BuildStep>,
+        ) {
+            for entry in entries {
+                match entry {
+                    BuildEntry::Step(step) => steps.push(*step),
+                    BuildEntry::Branch(branch) if holds(&branch.condition) => {
+                        flatten(&branch.then, holds, steps)
+                    }
+                    BuildEntry::Branch(branch) => flatten(&branch.otherwise, holds, steps),
+                }
+            }
+        }
+        let mut steps = Vec::new();
+        flatten(&self.entries, &holds, &mut steps);
+        steps
+    }
+}
+
+fn parse_entries(
+    step: &mut usize,
+    entries: &[StepEntry],
+) -> Result<Vec<BuildEntry>, BuildOrderError> {
+    entries
+        .iter()
+        .map(|entry| {
+            *step += 1;
+            match entry {
+                StepEntry::Branch(branch) => Ok(BuildEntry::Branch(Branch {
+                    condition: parse_condition(*step, &branch.condition)?,
+                    then: parse_entries(step, &branch.then)?,
+                    otherwise: parse_entries(step, &branch.otherwise)?,
+                })),
+                _ => parse_step(*step, entry).map(BuildEntry::Step),
+            }
+        })
+        .collect()
+}
+
+fn parse_condition(step: usize, table: &ConditionTable) -> Result<Condition, BuildOrderError> {
+    let invalid_condition = |reason: &str| BuildOrderError::InvalidCondition {
+        step,
+        reason: reason.to_string(),
+    };
+    match (&table.enemy, &table.race, &table.strategy) {
+        (Some(unit), None, None) => {
+            let unit = parse_unit(unit)
+                .ok_or_else(|| invalid_condition(&format!("unknown unit type \"{}\"", unit)))?;
+            let before = table
+                .before
+                .as_ref()
+                .map(|time| {
+                    parse_time(time).ok_or_else(|| {
+                        invalid_condition(&format!("\"{}\" is not a time (m:ss)", time))
+                    })
+                })
+                .transpose()?;
+            Ok(Condition::EnemySeen {
+                unit,
+                count: table.count.unwrap_or(1),
+                before,
+            })
+        }
+        (None, Some(race), None) => {
+            if table.count.is_some() || table.before.is_some() {
+                return Err(invalid_condition("`count` and `before` require `enemy`"));
+            }
+            let race = match race.as_str() {
+                "Terran" => Race::Terran,
+                "Zerg" => Race::Zerg,
+                "Protoss" => Race::Protoss,
+                _ => return Err(invalid_condition(&format!("unknown race \"{}\"", race))),
+            };
+            Ok(Condition::EnemyRace(race))
+        }
+        (None, None, Some(strategy)) => {
+            if table.count.is_some() || table.before.is_some() {
+                return Err(invalid_condition("`count` and `before` require `enemy`"));
+            }
+            let strategy = EnemyStrategy::from_label(strategy).ok_or_else(|| {
+                invalid_condition(&format!(
+                    "unknown strategy \"{}\", expected one of {}",
+                    strategy,
+                    EnemyStrategy::labels()
+                ))
+            })?;
+            Ok(Condition::EnemyStrategy(strategy))
+        }
+        _ => Err(invalid_condition(
+            "exactly one of `enemy`, `race` and `strategy` must be given",
+        )),
+    }
+}
+
+fn parse_step(step: usize, entry: &StepEntry) -> Result<BuildStep, BuildOrderError> {
+    let invalid_trigger = |reason: &str| BuildOrderError::InvalidTrigger {
+        step,
+        reason: reason.to_string(),
+    };
+    let (name, trigger) = match entry {
+        StepEntry::Branch(_) => unreachable!("Branches are parsed by `parse_entries`"),
+        StepEntry::Name(name) => match name.split_once(' ') {
+            // Standard supply notation, e.g. "14 SupplyDepot"
+            Some((supply, name)) => {
+                let supply = supply.parse().map_err(|_| {
+                    invalid_trigger(&format!("\"{}\" is not a supply count", supply))
+                })?;
+                (name.trim(), Some(Trigger::Supply(supply)))
+            }
+            None => (name.as_str(), None),
+        },
+        StepEntry::Table(table) => (
+            table.unit.as_str(),
+            table
+                .trigger
+                .parse()
+                .map_err(|reason| invalid_trigger(&reason))?,
+        ),
+    };
+
+    let unit = parse_unit(name).ok_or_else(|| BuildOrderError::UnknownUnit {
+        step,
+        name: name.to_string(),
+    })?;
+    if producer_of(unit).is_none() {
+        return Err(BuildOrderError::ImpossibleStep { step, unit });
+    }
+    Ok(BuildStep { unit, trigger })
+}
+
+/// Parses the trigger fields of a table, returning why they are invalid otherwise
+fn parse_trigger(
+    supply: Option<u32>,
+    time: Option<&str>,
+    when: Option<&str>,
+    percent: Option<f32>,
+) -> Result<Option<Trigger>, String> {
+    if percent.is_some() && when.is_none() {
+        return Err("`percent` requires `when`".to_string());
+    }
+    match (supply, time, when) {
+        (None, None, None) => Ok(None),
+        (Some(supply), None, None) => Ok(Some(Trigger::Supply(supply))),
+        (None, Some(time), None) => parse_time(time)
+            .map(|time| Some(Trigger::Time(time)))
+            .ok_or_else(|| format!("\"{}\" is not a time (m:ss)", time)),
+        (None, None, Some(structure)) => {
+            let structure = parse_unit(structure)
+                .filter(|s| s.is_structure())
+                .ok_or_else(|| format!("\"{}\" is not a structure", structure))?;
+            let percent = percent.unwrap_or(100.0);
+            if !(0.0..=100.0).contains(&percent) {
+                return Err("`percent` must be between 0 and 100".to_string());
+            }
+            Ok(Some(Trigger::Progress(structure, percent / 100.0)))
+        }
+        _ => Err("only one of `supply`, `time` and `when` can be given".to_string()),
+    }
+}
+
+/// Parses a game time written as `m:ss` into seconds
+fn parse_time(time: &str) -> Option<f32> {
+    let (minutes, seconds) = time.split_once(':')?;
+    let minutes: u32 = minutes.trim().parse().ok()?;
+    let seconds: u32 = seconds.trim().parse().ok()?;
+    if seconds >= 60 {
+        return None;
+    }
+    minutes
+        .checked_mul(60)
+        .and_then(|s| s.checked_add(seconds))
+        .map(|s| s as f32)
+}
+
+/// Parses a `UnitTypeId` from its variant name, e.g. `"SupplyDepot"`
+pub(crate) fn parse_unit(name: &str) -> Option<UID> {
+    let deserializer: StrDeserializer<'_, serde::de::value::Error> = name.into_deserializer();
+    UID::deserialize(deserializer).ok()
+}
+
+/// Parses an `UpgradeId` from its variant name, e.g. `"Stimpack"`
+pub(crate) fn parse_upgrade(name: &str) -> Option<UpgradeId> {
+    let deserializer: StrDeserializer<'_, serde::de::value::Error> = name.into_deserializer();
+    UpgradeId::deserialize(deserializer).ok()
+}
+
+/// Returns the unit type which produces `unit`, or `None` if it cannot be produced by Terran
+pub(crate) fn producer_of(unit: UID) -> Option<UID> {
+    match unit {
+        UID::CommandCenter
+        | UID::SupplyDepot
+        | UID::Refinery
+        | UID::Barracks
+        | UID::EngineeringBay
+        | UID::Bunker
+        | UID::MissileTurret
+        | UID::SensorTower
+        | UID::Factory
+        | UID::GhostAcademy
+        | UID::Armory
+        | UID::Starport
+        | UID::FusionCore => Some(UID::SCV),
+        UID::OrbitalCommand | UID::PlanetaryFortress => Some(UID::CommandCenter),
+        UID::BarracksReactor | UID::BarracksTechLab => Some(UID::Barracks),
+        UID::FactoryReactor | UID::FactoryTechLab => Some(UID::Factory),
+        UID::StarportReactor | UID::StarportTechLab => Some(UID::Starport),
+        _ => PRODUCERS.get(&unit).copied().filter(|producer| {
+            matches!(
+                producer,
+                UID::CommandCenter | UID::Barracks | UID::Factory | UID::Starport
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("2:30"), Some(150.0));
+        assert_eq!(parse_time("0:05"), Some(5.0));
+        assert_eq!(parse_time("1:60"), None);
+        assert_eq!(parse_time("90"), None);
+        assert_eq!(parse_time("a:00"), None);
+        assert_eq!(parse_time("99999999:00"), None);
+    }
+
+    #[test]
+    fn parses_triggers() {
+        assert_eq!(parse_trigger(None, None, None, None), Ok(None));
+        assert_eq!(
+            parse_trigger(Some(14), None, None, None),
+            Ok(Some(Trigger::Supply(14)))
+        );
+        assert_eq!(
+            parse_trigger(None, Some("2:30"), None, None),
+            Ok(Some(Trigger::Time(150.0)))
+        );
+        assert_eq!(
+            parse_trigger(None, None, Some("Barracks"), None),
+            Ok(Some(Trigger::Progress(UID::Barracks, 1.0)))
+        );
+        assert_eq!(
+            parse_trigger(None, None, Some("SupplyDepot"), Some(50.0)),
+            Ok(Some(Trigger::Progress(UID::SupplyDepot, 0.5)))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_triggers() {
+        assert!(parse_trigger(Some(14), Some("2:30"), None, None).is_err());
+        assert!(parse_trigger(None, Some("2:75"), None, None).is_err());
+        assert!(parse_trigger(None, None, Some("Marine"), None).is_err());
+        assert!(parse_trigger(None, None, Some("Barracks"), Some(150.0)).is_err());
+        assert!(parse_trigger(None, None, None, Some(50.0)).is_err());
+        assert!(parse_trigger(Some(14), None, None, Some(50.0)).is_err());
+        assert!(parse_trigger(None, Some("2:30"), None, Some(50.0)).is_err());
+    }
+
+    #[test]
+    fn parses_steps_and_branches() {
+        let build_order = BuildOrder::parse(
+            r#"
+            name = "Test"
+            steps = [
+                "14 SupplyDepot",
+                { unit = "Barracks", when = "SupplyDepot", percent = 50 },
+                { if = { enemy = "Hatchery", count = 2, before = "1:30" }, then = ["Bunker"], else = ["Factory"] },
+            ]
+            "#,
+        )
+        .unwrap();
+        let units = |steps: Vec<BuildStep>| steps.iter().map(|s| s.unit).collect::<Vec<_>>();
+        let steps = build_order.active_steps(|_| true);
+        assert_eq!(steps[0].trigger, Some(Trigger::Supply(14)));
+        assert_eq!(
+            steps[1].trigger,
+            Some(Trigger::Progress(UID::SupplyDepot, 0.5))
+        );
+        assert_eq!(units(steps), [UID::SupplyDepot, UID::Barracks, UID::Bunker]);
+        assert_eq!(
+            units(build_order.active_steps(|_| false)),
+            [UID::SupplyDepot, UID::Barracks, UID::Factory]
+        );
+    }
+
+    #[test]
+    fn parses_scout_triggers() {
+        let parse = |scout: &str| {
+            BuildOrder::parse(&format!("name = \"Test\"\nsteps = []\nscout = {}", scout))
+        };
+        assert_eq!(
+            parse("{ when = \"SupplyDepot\", percent = 0 }")
+                .unwrap()
+                .scout,
+            Some(Trigger::Progress(UID::SupplyDepot, 0.0))
+        );
+        assert!(matches!(
+            parse("{}"),
+            Err(BuildOrderError::InvalidScoutTrigger(_))
+        ));
+        assert!(matches!(
+            parse("{ supply = 14, precent = 50 }"),
+            Err(BuildOrderError::InvalidScoutTrigger(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_step_fields() {
+        assert!(matches!(
+            BuildOrder::parse(
+                "name = \"Test\"\nsteps = [{ unit = \"Barracks\", supply = 16, tme = \"2:00\" }]"
+            ),
+            Err(BuildOrderError::InvalidTrigger { step: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn sorts_tech_scans() {
+        let build_order = BuildOrder::parse(
+            "name = \"Test\"\nsteps = []\nscans = { tech = [\"8:00\", \"5:00\"] }",
+        )
+        .unwrap();
+        assert_eq!(build_order.tech_scans, [300.0, 480.0]);
+    }
+
+    #[test]
+    fn names_the_bad_field_of_a_malformed_branch() {
+        let parse = |branch: &str| match BuildOrder::parse(&format!(
+            "name = \"Test\"\nsteps = [{}]",
+            branch
+        )) {
+            Err(BuildOrderError::Parse(e)) => e.to_string(),
+            other => panic!("expected a parse error, got {:?}", other.map(|b| b.name)),
+        };
+        assert!(
+            parse(r#"{ if = { race = "Zerg" }, then = ["Bunker"], els = ["Factory"] }"#)
+                .contains("els")
+        );
+        assert!(parse(r#"{ if = { rcae = "Zerg" }, then = ["Bunker"] }"#).contains("rcae"));
+    }
+
+    #[test]
+    fn rejects_unknown_units() {
+        assert!(matches!(
+            BuildOrder::parse("name = \"Test\"\nsteps = [\"SupplyDepot\", \"Supplydepot\"]"),
+            Err(BuildOrderError::UnknownUnit { step: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::bot::{format_time, BuildError, Tag, TerranBot};
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Game seconds a build command may take to start on top of the builder's walking time, before
+/// it is given up on and issued again
+const BUILD_START_SLACK: f32 = 5.0;
+/// Game seconds a location where a build failed is avoided, after which it is tried again
+const FAILED_LOCATION_TIMEOUT: f32 = 60.0;
+
+/// A build or train command which has been issued but not yet seen to start
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingBuild {
+    pub(crate) unit: UID,
+    /// The worker building the structure, or the structure producing the unit
+    pub(crate) builder: Tag,
+    /// Where the structure is placed, `None` for units, addons and morphs
+    pub(crate) location: Option<Point2>,
+    pub(crate) deadline: f32,
+}
+
+impl TerranBot {
+    /// Remembers an issued build command so it can be confirmed or retried by
+    /// `check_pending_builds`
+    pub(crate) fn track_build(&mut self, unit: UID, builder: Tag, location: Option<Point2>) {
+        let walking_time = self
+            .units
+            .my
+            .workers
+            .get(builder)
+            .zip(location)
+            .map_or(0.0, |(worker, location)| {
+                worker.distance(location) / worker.real_speed()
+            });
+        self.pending_builds.push(PendingBuild {
+            unit,
+            builder,
+            location,
+            deadline: self.time + walking_time + BUILD_START_SLACK,
+        });
+    }
+
+    /// Confirms pending builds which have started, and gives up on those which failed so the
+    /// build order issues them again. Returns why each failed build did not start.
+    pub(crate) fn check_pending_builds(&mut self) -> Vec<BuildError> {
+        let mut started = Vec::new();
+        let mut failed = Vec::new();
+        let now = self.time;
+        self.failed_locations
+            .retain(|&(_, failed_at)| now - failed_at < FAILED_LOCATION_TIMEOUT);
+        let pending = std::mem::take(&mut self.pending_builds);
+        for build in pending {
+            let builder = self.units.my.all.get(build.builder);
+            if self.has_build_started(&build) {
+                started.push(build.unit);
+            } else if builder.is_none() {
+                failed.push(BuildError::BuilderLost(build.unit));
+            } else if !builder.is_some_and(|b| self.is_ordered_to_build(b, build.unit)) {
+                // The command was rejected, usually because the location became blocked
+                if let Some(location) = build.location {
+                    self.failed_locations.push((location, now));
+                }
+                failed.push(BuildError::CommandDropped(build.unit, build.location));
+            } else if now > build.deadline {
+                // Stop the builder, so the build no longer counts as ordered
+                if let Some(builder) = builder {
+                    builder.stop(false);
+                }
+                // The builder may be stuck, or the location unreachable
+                if let Some(location) = build.location {
+                    self.failed_locations.push((location, now));
+                }
+                failed.push(BuildError::BuildTimedOut(build.unit));
+            } else {
+                self.pending_builds.push(build);
+            }
+        }
+
+        let time = format_time(self.time);
+        for unit in started {
+            self.log(&format!(
+                "{} {:?}: construction started (m: {}, g: {} {}/{})",
+                time, unit, self.minerals, self.vespene, self.supply_used, self.supply_cap
+            ));
+        }
+        failed
+    }
+
+    /// Returns true if the structure has been placed at its location, or the producer has the
+    /// unit in its queue
+    fn has_build_started(&self, build: &PendingBuild) -> bool {
+        match build.location {
+            Some(location) => self
+                .units
+                .my
+                .structures
+                .iter()
+                .of_type(build.unit)
+                .any(|s| s.distance(location) < 1.0),
+            None => self
+                .units
+                .my
+                .structures
+                .get(build.builder)
+                .is_some_and(|producer| self.is_ordered_to_build(producer, build.unit)),
+        }
+    }
+
+    fn is_ordered_to_build(&self, builder: &Unit, unit: UID) -> bool {
+        self.game_data
+            .units
+            .get(&unit)
+            .and_then(|data| data.ability)
+            .is_some_and(|ability| {
+                builder
+                    .orders()
+                    .iter()
+                    .any(|order| order.ability == ability)
+            })
+    }
+
+    /// Returns true if an attempt to build at the location failed recently
+    pub(crate) fn is_failed_location(&self, location: Point2) -> bool {
+        self.failed_locations
+            .iter()
+            .any(|&(failed, _)| failed.distance(location) < 1.0)
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{
+    bot::{format_time, TerranBot},
+    unit_data::BURNING_HEALTH,
+};
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Enemy units and structures which mean cloaked or burrowed units are coming
+const CLOAK_TECH: &[UID] = &[
+    UID::DarkShrine,
+    UID::DarkTemplar,
+    UID::Banshee,
+    UID::WidowMine,
+    UID::LurkerDenMP,
+    UID::LurkerMPEgg,
+    UID::LurkerMP,
+    UID::LurkerMPBurrowed,
+];
+/// An attacked unit with no visible enemy this close was hit by something we cannot see
+const UNSEEN_ATTACKER_RADIUS: f32 = 13.0;
+/// Enemies seen this many game seconds ago may still be attacking from the fog
+const RECENTLY_SEEN: f32 = 5.0;
+/// Radius in which our detectors reveal cloaked units
+const DETECTION_RANGE: f32 = 11.0;
+/// Ravens keep this close to the center of the army
+const RAVEN_ESCORT_DISTANCE: f32 = 3.0;
+
+impl TerranBot {
+    /// Notices cloaked threats, and answers them with an Engineering Bay, a Missile Turret at
+    /// every base and a Raven with the army. Fights against them are scanned by
+    /// `process_orbitals`.
+    pub(crate) fn process_detection(&mut self) {
+        if self.cloak_noticed_at.is_none() {
+            let Some(reason) = self.cloak_threat() else {
+                return;
+            };
+            self.log(&format!(
+                "{} Cloaked threat: {}",
+                format_time(self.time),
+                reason
+            ));
+            self.cloak_noticed_at = Some(self.time);
+        }
+        self.build_detection();
+        self.train_raven();
+    }
+
+    /// Returns what points to cloaked or burrowed enemies, if anything does. Attacks by unseen
+    /// enemies may come from the fog just as well, so they are answered with scans only.
+    fn cloak_threat(&self) -> Option<String> {
+        if let Some(enemy) = self
+            .enemy_memory
+            .all()
+            .iter()
+            .find(|e| e.is_cloaked() || e.is_burrowed())
+        {
+            return Some(format!("{:?} seen", enemy.type_id()));
+        }
+        self.enemies_seen
+            .values()
+            .find(|(unit, _)| CLOAK_TECH.contains(unit))
+            .map(|(unit, _)| format!("{:?} scouted", unit))
+    }
+
+    /// Returns where our units or structures are attacked with no enemy seen around lately, and
+    /// no detector of ours reveals the attacker
+    pub(crate) fn unseen_attack_positions(&self) -> Vec<Point2> {
+        self.units
+            .my
+            .all
+            .iter()
+            .filter(|u| u.is_attacked())
+            // Burning structures lose health by themselves, which counts as being attacked
+            .filter(|u| {
+                !u.is_structure() || u.health_percentage().is_some_and(|h| h >= BURNING_HEALTH)
+            })
+            .filter(|u| {
+                self.units
+                    .enemy
+                    .all
+                    .iter()
+                    .filter(|e| e.is_visible() && e.can_attack())
+                    .all(|e| !e.is_closer(UNSEEN_ATTACKER_RADIUS, *u))
+            })
+            .filter(|u| {
+                self.enemy_memory
+                    .snapshots
+                    .values()
+                    .filter(|s| self.time - s.last_seen < RECENTLY_SEEN)
+                    .filter(|s| {
+                        s.unit.can_attack() && !s.unit.is_cloaked() && !s.unit.is_burrowed()
+                    })
+                    .all(|s| !s.unit.is_closer(UNSEEN_ATTACKER_RADIUS, *u))
+            })
+            .map(|u| u.position())
+            .filter(|&position| !self.is_detected(position))
+            .collect()
+    }
+
+    /// Returns true if one of our detectors reveals cloaked units at the position
+    pub(crate) fn is_detected(&self, position: Point2) -> bool {
+        self.units
+            .my
+            .all
+            .iter()
+            .of_types(&vec![UID::Raven, UID::MissileTurret])
+            .ready()
+            .any(|d| d.is_closer(DETECTION_RANGE, position))
+    }
+
+    /// Builds an Engineering Bay, then a Missile Turret in the mineral line of every base
+    fn build_detection(&mut self) {
+        let structure = if in_production(self, UID::EngineeringBay) == 0 {
+            UID::EngineeringBay
+        } else if in_production(self, UID::MissileTurret) < self.units.my.townhalls.ready().len()
+            && self.counter().count(UID::EngineeringBay) > 0
+        {
+            UID::MissileTurret
+        } else {
+            return;
+        };
+        if self.pending_builds.iter().any(|b| b.unit == structure)
+            || !self.can_afford_unreserved(structure, false)
+        {
+            return;
+        }
+        if let Ok((builder, location)) = self.build_structure(structure) {
+            self.track_build(structure, builder, location);
+            self.subtract_resources(structure, false);
+        }
+    }
+
+    /// Keeps a Raven, adding a TechLab to a Starport for it if needed
+    fn train_raven(&mut self) {
+        if in_production(self, UID::Raven) > 0 || self.counter().count(UID::Starport) == 0 {
+            return;
+        }
+        let starport = self
+            .units
+            .my
+            .structures
+            .iter()
+            .of_type(UID::Starport)
+            .ready()
+            .filter(|s| s.has_techlab())
+            .almost_idle()
+            .next()
+            .map(|s| s.tag());
+        match starport {
+            Some(starport) if self.can_afford_unreserved(UID::Raven, true) => {
+                if let Some(starport) = self.units.my.structures.get(starport) {
+                    starport.train(UID::Raven, false);
+                }
+                self.subtract_resources(UID::Raven, true);
+            }
+            Some(_) => {}
+            None if in_production(self, UID::StarportTechLab) == 0
+                && self.can_afford_unreserved(UID::StarportTechLab, false) =>
+            {
+                if let Ok((producer, _)) = self.build_addon(UID::StarportTechLab) {
+                    self.track_build(UID::StarportTechLab, producer, None);
+                    self.subtract_resources(UID::StarportTechLab, false);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Keeps Ravens at the center of the army, so cloaked units fighting it are revealed
+    pub(crate) fn escort_army_with_ravens(&self) {
+        let army: Units = self
+            .units
+            .my
+            .units
+            .iter()
+            .filter(|u| !u.type_id().is_worker() && u.can_attack())
+            .cloned()
+            .collect();
+        let Some(center) = army.center() else {
+            return;
+        };
+        for raven in self.units.my.units.iter().of_type(UID::Raven) {
+            if !raven.is_closer(RAVEN_ESCORT_DISTANCE, center) {
+                raven.move_to(Target::Pos(center), false);
+            }
+        }
+    }
+}
+
+/// Returns how many of the unit exist or are being made
+fn in_production(bot: &TerranBot, unit: UID) -> usize {
+    bot.counter().all().count(unit)
+}
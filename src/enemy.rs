@@ -0,0 +1,59 @@
+use crate::{bot::TerranBot, build_order::Condition, exclusion::distance_to_segment};
+use rust_sc2::prelude::*;
+
+/// Enemies within this distance of a path make it unsafe for workers
+const PATH_THREAT_RADIUS: f32 = 10.0;
+
+impl TerranBot {
+    /// Remembers the type and time of first sighting of every enemy unit we see
+    pub(crate) fn record_seen_enemies(&mut self) {
+        let time = self.time;
+        let seen: Vec<_> = self
+            .units
+            .enemy
+            .all
+            .iter()
+            .map(|u| (u.tag(), u.type_id()))
+            .collect();
+        for (tag, type_id) in seen {
+            self.enemies_seen.entry(tag).or_insert((type_id, time));
+        }
+    }
+
+    /// Returns true if the build order branch condition holds given what we have seen of the enemy
+    pub(crate) fn condition_holds(&self, condition: &Condition) -> bool {
+        match *condition {
+            Condition::EnemySeen {
+                unit,
+                count,
+                before,
+            } => {
+                self.enemies_seen
+                    .values()
+                    .filter(|&&(seen_type, seen_time)| {
+                        seen_type == unit && before.is_none_or(|before| seen_time <= before)
+                    })
+                    .count()
+                    >= count
+            }
+            Condition::EnemyRace(race) => self.enemy_race == race,
+            Condition::EnemyStrategy(strategy) => self.is_enemy_strategy(strategy),
+        }
+    }
+
+    /// Returns true if an enemy which can attack ground units, other than a worker, is close to
+    /// the straight path between the two points
+    pub(crate) fn is_path_threatened(&self, from: Point2, to: Point2) -> bool {
+        self.units
+            .enemy
+            .units
+            .iter()
+            .filter(|u| is_ground_threat(u))
+            .any(|u| distance_to_segment(u.position(), from, to) < PATH_THREAT_RADIUS)
+    }
+}
+
+/// Returns true if the enemy can attack ground units and is not a worker
+pub(crate) fn is_ground_threat(unit: &Unit) -> bool {
+    unit.can_attack_ground() && !unit.type_id().is_worker()
+}
@@ -0,0 +1,116 @@
+use crate::bot::{Tag, TerranBot};
+use rust_sc2::prelude::*;
+use rustc_hash::FxHashMap;
+
+/// Seconds an enemy unit out of vision is remembered, unless configured otherwise
+pub(crate) const DEFAULT_MEMORY_DECAY: f32 = 30.0;
+
+/// The last sighting of an enemy unit
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+    /// The unit as it was last seen, with its position, type and health at that time
+    pub(crate) unit: Unit,
+    pub(crate) last_seen: f32,
+}
+
+/// Enemy units which were seen, kept after they leave vision
+#[derive(Debug, Clone)]
+pub(crate) struct EnemyMemory {
+    pub(crate) snapshots: FxHashMap<Tag, Snapshot>,
+    /// Seconds a unit out of vision is remembered. Structures do not move, so they are kept
+    /// until they are destroyed or seen to be gone.
+    pub(crate) decay: f32,
+}
+
+impl Default for EnemyMemory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEMORY_DECAY)
+    }
+}
+
+impl EnemyMemory {
+    pub(crate) fn new(decay: f32) -> Self {
+        Self {
+            snapshots: FxHashMap::default(),
+            decay,
+        }
+    }
+
+    pub(crate) fn forget(&mut self, tag: Tag) {
+        self.snapshots.remove(&tag);
+    }
+
+    /// Returns every remembered enemy as it was last seen
+    pub(crate) fn all(&self) -> Units {
+        self.snapshots.values().map(|s| s.unit.clone()).collect()
+    }
+
+    /// Returns the remembered enemy units, without structures
+    pub(crate) fn units(&self) -> Units {
+        self.snapshots
+            .values()
+            .filter(|s| !s.unit.is_structure())
+            .map(|s| s.unit.clone())
+            .collect()
+    }
+
+    pub(crate) fn structures(&self) -> Units {
+        self.snapshots
+            .values()
+            .filter(|s| s.unit.is_structure())
+            .map(|s| s.unit.clone())
+            .collect()
+    }
+}
+
+impl TerranBot {
+    /// Records every enemy in vision, and forgets units not seen for longer than the decay or
+    /// missing from where they were last seen
+    pub(crate) fn remember_enemies(&mut self) {
+        let time = self.time;
+        let seen: Vec<_> = self
+            .units
+            .enemy
+            .all
+            .iter()
+            .filter(|u| u.is_visible())
+            .cloned()
+            .collect();
+        for unit in seen {
+            self.enemy_memory.snapshots.insert(
+                unit.tag(),
+                Snapshot {
+                    unit,
+                    last_seen: time,
+                },
+            );
+        }
+
+        let decay = self.enemy_memory.decay;
+        let forgotten: Vec<_> = self
+            .enemy_memory
+            .snapshots
+            .iter()
+            .filter(|(_, s)| s.last_seen < time)
+            .filter(|(_, s)| {
+                // Missing from where it would be in vision, so it moved or died unseen
+                self.is_visible(s.unit.position())
+                    || (!s.unit.is_structure() && time - s.last_seen > decay)
+            })
+            .map(|(&tag, _)| tag)
+            .collect();
+        for tag in forgotten {
+            self.enemy_memory.forget(tag);
+        }
+    }
+
+    /// Returns how to target a remembered enemy: the unit itself while it is in vision, where it
+    /// was last seen otherwise
+    pub(crate) fn enemy_target(&self, enemy: &Unit) -> Target {
+        if self.units.enemy.all.contains_tag(enemy.tag()) {
+            Target::Tag(enemy.tag())
+        } else {
+            Target::Pos(enemy.position())
+        }
+    }
+}
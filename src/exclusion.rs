@@ -0,0 +1,73 @@
+use crate::bot::TerranBot;
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Space kept free around mineral fields and geysers
+const RESOURCE_CLEARANCE: f32 = 3.0;
+/// Space kept free on each side of the path from a townhall to its resources
+const MINING_CLEARANCE: f32 = 1.0;
+/// Half the width of the corridor kept free through the main ramp
+const RAMP_CORRIDOR: f32 = 2.5;
+/// How far the ramp corridor reaches into the main beyond the top of the ramp
+const RAMP_CORRIDOR_REACH: f32 = 4.0;
+
+impl TerranBot {
+    /// Returns true if a structure at the location would hinder mining or block the main ramp:
+    /// it is close to a resource, between a base and its resources, or in the ramp corridor.
+    /// Turrets may stand in mineral lines.
+    pub(crate) fn is_excluded(&self, structure: UID, location: Point2) -> bool {
+        let radius = structure_radius(structure);
+        let mut footprint = vec![(location, radius)];
+        if matches!(structure, UID::Barracks | UID::Factory | UID::Starport) {
+            footprint.push((location.offset(2.5, -0.5), 1.0));
+        }
+        footprint.into_iter().any(|(center, radius)| {
+            (structure != UID::MissileTurret && self.is_in_mining_area(center, radius))
+                || self.is_in_ramp_corridor(center, radius)
+        })
+    }
+
+    fn is_in_mining_area(&self, center: Point2, radius: f32) -> bool {
+        self.expansions.iter().any(|expansion| {
+            expansion
+                .minerals
+                .iter()
+                .chain(&expansion.geysers)
+                .filter_map(|&tag| self.units.resources.get(tag))
+                .any(|resource| {
+                    resource.is_closer(RESOURCE_CLEARANCE + radius, center)
+                        || distance_to_segment(center, expansion.loc, resource.position())
+                            < MINING_CLEARANCE + radius
+                })
+        })
+    }
+
+    fn is_in_ramp_corridor(&self, center: Point2, radius: f32) -> bool {
+        let ramp = &self.ramps.my;
+        let (Some(top), Some(bottom)) = (ramp.top_center(), ramp.bottom_center()) else {
+            return false;
+        };
+        let (top, bottom): (Point2, Point2) = (top.into(), bottom.into());
+        let inside = top.towards(bottom, -RAMP_CORRIDOR_REACH);
+        distance_to_segment(center, bottom, inside) < RAMP_CORRIDOR + radius
+    }
+}
+
+/// Returns half the width of the structure's footprint
+fn structure_radius(structure: UID) -> f32 {
+    match structure {
+        UID::SensorTower => 0.5,
+        UID::SupplyDepot | UID::MissileTurret => 1.0,
+        UID::CommandCenter => 2.5,
+        _ => 1.5,
+    }
+}
+
+/// Returns the distance from the point to the closest point on the segment between `from` and
+/// `to`
+pub(crate) fn distance_to_segment(point: Point2, from: Point2, to: Point2) -> f32 {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let length_squared = (dx * dx + dy * dy).max(f32::EPSILON);
+    let t = (((point.x - from.x) * dx + (point.y - from.y) * dy) / length_squared).clamp(0.0, 1.0);
+    point.distance(Point2::new(from.x + dx * t, from.y + dy * t))
+}
@@ -0,0 +1,110 @@
+use crate::bot::TerranBot;
+use rust_sc2::prelude::*;
+
+/// How much being far from the enemy weighs against being far from the main
+const ENEMY_DISTANCE_WEIGHT: f32 = 0.5;
+/// How much being far from our other bases, which makes a base harder to defend, weighs
+const DEFENSE_DISTANCE_WEIGHT: f32 = 0.5;
+/// Expansions with an enemy townhall this close are taken
+const TAKEN_RADIUS: f32 = 6.0;
+
+/// How good a free expansion is to take next, the lower the score the better
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExpansionScore {
+    pub(crate) location: Point2,
+    /// Ground distance from the main
+    pub(crate) path_distance: f32,
+    /// Distance to the closest known enemy base
+    pub(crate) enemy_distance: f32,
+    /// Distance to our closest townhall, where defenders come from
+    pub(crate) defense_distance: f32,
+    pub(crate) score: f32,
+}
+
+impl TerranBot {
+    /// Returns the free expansions not taken by the enemy, best first
+    pub(crate) fn expansion_ranking(&self) -> Vec<ExpansionScore> {
+        let enemy_bases: Vec<Point2> = self
+            .units
+            .enemy
+            .townhalls
+            .iter()
+            .map(|t| t.position())
+            .chain([self.enemy_start])
+            .collect();
+        let free: Vec<_> = self
+            .free_expansions()
+            .filter(|e| !enemy_bases.iter().any(|b| b.is_closer(TAKEN_RADIUS, e.loc)))
+            .map(|e| e.loc)
+            .collect();
+
+        let mut ranking: Vec<_> = free
+            .iter()
+            .map(|&location| {
+                let path_distance = self
+                    .expansion_distances
+                    .iter()
+                    .find(|&&(expansion, _)| expansion == location)
+                    .map_or_else(|| self.main_exit().distance(location), |&(_, d)| d);
+                let enemy_distance = enemy_bases
+                    .iter()
+                    .map(|b| b.distance(location))
+                    .fold(f32::INFINITY, f32::min);
+                let defense_distance = self
+                    .units
+                    .my
+                    .townhalls
+                    .iter()
+                    .map(|t| t.distance(location))
+                    .fold(f32::INFINITY, f32::min);
+                ExpansionScore {
+                    location,
+                    path_distance,
+                    enemy_distance,
+                    defense_distance,
+                    score: path_distance - ENEMY_DISTANCE_WEIGHT * enemy_distance
+                        + DEFENSE_DISTANCE_WEIGHT * defense_distance,
+                }
+            })
+            .collect();
+        ranking.sort_by(|a, b| a.score.total_cmp(&b.score));
+        ranking
+    }
+
+    /// Returns the ground distance from the main to every expansion. Pathing queries block the
+    /// game, so this is done once at the start.
+    pub(crate) fn measure_expansion_distances(&self) -> Vec<(Point2, f32)> {
+        let main_exit = self.main_exit();
+        let locations: Vec<_> = self.expansions.iter().map(|e| e.loc).collect();
+        let path_distances = self
+            .query_pathing(
+                locations
+                    .iter()
+                    .map(|&location| (Target::Pos(main_exit), location))
+                    .collect(),
+            )
+            .unwrap_or_default();
+        locations
+            .iter()
+            .enumerate()
+            .map(|(i, &location)| {
+                let distance = path_distances
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or_else(|| main_exit.distance(location));
+                (location, distance)
+            })
+            .collect()
+    }
+
+    /// The start location is inside our Command Center, so paths start from just outside of it
+    fn main_exit(&self) -> Point2 {
+        self.start_location.towards(self.game_info.map_center, 4.0)
+    }
+
+    /// Returns the location of the best expansion to take next
+    pub(crate) fn best_expansion(&self) -> Option<Point2> {
+        self.expansion_ranking().first().map(|e| e.location)
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{
+    base::{GAS_INCOME_PER_WORKER, MINERAL_INCOME_PER_WORKER},
+    bot::{Tag, TerranBot},
+};
+use rust_sc2::prelude::*;
+
+/// Game seconds of history the income is measured over
+const INCOME_WINDOW: f32 = 20.0;
+/// Game seconds of history needed before the measured income is trusted
+const MIN_INCOME_WINDOW: f32 = 5.0;
+
+/// Minerals and gas gathered in total at some point in time
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IncomeSample {
+    pub(crate) time: f32,
+    pub(crate) minerals: f32,
+    pub(crate) vespene: f32,
+}
+
+impl TerranBot {
+    /// Records the resources gathered so far. Must be called before anything is spent in the
+    /// step, as spending is only added back once the game reports it.
+    pub(crate) fn record_income(&mut self) {
+        let score = &self.state.observation.score;
+        let sample = IncomeSample {
+            time: self.time,
+            // Adding back what was spent leaves what was gathered
+            minerals: self.minerals as f32 + score.spent_minerals,
+            vespene: self.vespene as f32 + score.spent_vespene,
+        };
+        let time = self.time;
+        self.income_samples.push_back(sample);
+        while self
+            .income_samples
+            .front()
+            .is_some_and(|s| time - s.time > INCOME_WINDOW)
+        {
+            self.income_samples.pop_front();
+        }
+    }
+
+    /// Returns the minerals and gas gathered per game second over the last `INCOME_WINDOW`
+    /// seconds, or `None` if there is not enough history yet
+    fn measured_income(&self) -> Option<(f32, f32)> {
+        let first = self.income_samples.front()?;
+        let last = self.income_samples.back()?;
+        let elapsed = last.time - first.time;
+        (elapsed >= MIN_INCOME_WINDOW).then(|| {
+            (
+                (last.minerals - first.minerals).max(0.0) / elapsed,
+                (last.vespene - first.vespene).max(0.0) / elapsed,
+            )
+        })
+    }
+
+    /// Minerals gathered per game second
+    pub(crate) fn mineral_income(&self) -> f32 {
+        match self.measured_income() {
+            Some((minerals, _)) => minerals,
+            None => {
+                self.harvesters_on(|tag| self.units.mineral_fields.contains_tag(tag)) as f32
+                    * MINERAL_INCOME_PER_WORKER
+            }
+        }
+    }
+
+    /// Vespene gathered per game second
+    pub(crate) fn gas_income(&self) -> f32 {
+        match self.measured_income() {
+            Some((_, vespene)) => vespene,
+            None => {
+                self.harvesters_on(|tag| self.units.my.gas_buildings.contains_tag(tag)) as f32
+                    * GAS_INCOME_PER_WORKER
+            }
+        }
+    }
+
+    /// Counts workers targeting a resource, to estimate income before it can be measured
+    fn harvesters_on(&self, is_resource: impl Fn(Tag) -> bool) -> usize {
+        self.units
+            .my
+            .workers
+            .iter()
+            .filter(|w| w.target_tag().is_some_and(&is_resource))
+            .count()
+    }
+}
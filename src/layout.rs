@@ -0,0 +1,173 @@
+use crate::bot::TerranBot;
+use rust_sc2::prelude::*;
+use rustc_hash::FxHashSet;
+use UnitTypeId as UID;
+
+/// Production slots and depots are planned within this distance of the start location
+const LAYOUT_RADIUS: i32 = 22;
+/// Horizontal distance between production slots, a building and its addon plus a lane
+const PRODUCTION_COLUMN: i32 = 7;
+/// Vertical distance between production slots, a building plus a lane
+const PRODUCTION_ROW: i32 = 4;
+/// Depots planned in the depot zone
+const MAX_DEPOTS: usize = 16;
+/// Distance of the turret slot from the townhall towards its minerals
+const TURRET_DISTANCE: f32 = 4.5;
+
+/// Where structures are placed, planned when the game starts
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BaseLayout {
+    /// Centers of 3x3 production buildings, each with room for an addon to its right
+    pub(crate) production: Vec<Point2>,
+    /// Centers of depots, along the edge of the main away from the map center
+    pub(crate) depots: Vec<Point2>,
+    /// Centers of missile turrets, one in each mineral line
+    pub(crate) turrets: Vec<Point2>,
+}
+
+impl TerranBot {
+    /// Plans production slots and a depot zone in the main, and a turret slot at every base
+    pub(crate) fn plan_layout(&self) -> BaseLayout {
+        let origin = self.start_location;
+        let height = self.get_height((origin.x as usize, origin.y as usize));
+        let mut reserved = FxHashSet::default();
+        // Keep the ramp wall free
+        if let Some(wall) = self.ramp_wall {
+            for depot in wall.depots {
+                reserved.extend(footprint(depot, 2));
+            }
+            reserved.extend(footprint(wall.barracks, 3));
+            reserved.extend(footprint(wall.barracks.offset(2.5, -0.5), 2));
+        }
+
+        let mut production = Vec::new();
+        for dy in (-LAYOUT_RADIUS..=LAYOUT_RADIUS).step_by(PRODUCTION_ROW as usize) {
+            for dx in (-LAYOUT_RADIUS..=LAYOUT_RADIUS).step_by(PRODUCTION_COLUMN as usize) {
+                let center = Point2::new(
+                    origin.x.floor() + dx as f32 + 0.5,
+                    origin.y.floor() + dy as f32 + 0.5,
+                );
+                let tiles: Vec<_> = footprint(center, 3)
+                    .chain(footprint(center.offset(2.5, -0.5), 2))
+                    .collect();
+                if self.is_free_area(&tiles, height, &reserved) {
+                    reserved.extend(tiles);
+                    production.push(center);
+                }
+            }
+        }
+        production.sort_by(|a, b| a.distance(origin).total_cmp(&b.distance(origin)));
+
+        let mut depots = Vec::new();
+        for dy in (-LAYOUT_RADIUS..=LAYOUT_RADIUS).step_by(2) {
+            for dx in (-LAYOUT_RADIUS..=LAYOUT_RADIUS).step_by(2) {
+                let center =
+                    Point2::new(origin.x.floor() + dx as f32, origin.y.floor() + dy as f32);
+                if self.is_free_area(&footprint(center, 2).collect::<Vec<_>>(), height, &reserved) {
+                    depots.push(center);
+                }
+            }
+        }
+        // Depots closest to the map edge first, away from where the enemy comes from
+        let map_center = self.game_info.map_center;
+        depots.sort_by(|a, b| b.distance(map_center).total_cmp(&a.distance(map_center)));
+        depots.truncate(MAX_DEPOTS);
+
+        let turrets = self
+            .expansions
+            .iter()
+            .map(|expansion| {
+                let turret = expansion.loc.towards(expansion.center, TURRET_DISTANCE);
+                Point2::new(turret.x.round(), turret.y.round())
+            })
+            .collect();
+
+        BaseLayout {
+            production,
+            depots,
+            turrets,
+        }
+    }
+
+    /// Returns the first free slot in the layout for the structure, `None` if it has no slot
+    pub(crate) fn find_layout_location(&self, structure: UID) -> Option<Point2> {
+        let slots = match structure {
+            UID::SupplyDepot => &self.layout.depots,
+            UID::MissileTurret => &self.layout.turrets,
+            // Tech structures are the size of production buildings, so they share their slots
+            UID::Barracks
+            | UID::Factory
+            | UID::Starport
+            | UID::EngineeringBay
+            | UID::Armory
+            | UID::GhostAcademy
+            | UID::FusionCore => &self.layout.production,
+            _ => return None,
+        };
+        let turret_bases: Vec<_> = self
+            .units
+            .my
+            .townhalls
+            .iter()
+            .map(|t| t.position())
+            .collect();
+        slots.iter().copied().find(|&slot| {
+            // Turrets only go to mineral lines of bases we have
+            (structure != UID::MissileTurret
+                || turret_bases
+                    .iter()
+                    .any(|base| base.distance(slot) < 2.0 * TURRET_DISTANCE))
+                && !self.is_slot_taken(slot)
+                && !self.is_failed_location(slot)
+                && !self.is_excluded(structure, slot)
+                && self.can_place(structure, slot)
+        })
+    }
+
+    /// Returns true if a structure stands at the slot, or a builder is on its way there
+    pub(crate) fn is_slot_taken(&self, slot: Point2) -> bool {
+        self.units
+            .my
+            .structures
+            .iter()
+            .any(|s| s.is_closer(1.0, slot))
+            || self
+                .pending_builds
+                .iter()
+                .any(|b| b.location.is_some_and(|l| l.distance(slot) < 1.0))
+    }
+
+    /// Returns true if every tile can be built on at the height of the main, is not reserved
+    /// and is away from resources
+    fn is_free_area(
+        &self,
+        tiles: &[(usize, usize)],
+        height: u8,
+        reserved: &FxHashSet<(usize, usize)>,
+    ) -> bool {
+        let area = &self.game_info.playable_area;
+        tiles.iter().all(|&(x, y)| {
+            (area.x0..area.x1).contains(&x)
+                && (area.y0..area.y1).contains(&y)
+                && !reserved.contains(&(x, y))
+                && self.is_placeable((x, y))
+                && self.get_height((x, y)) == height
+                && !self.is_near_resources(Point2::new(x as f32 + 0.5, y as f32 + 0.5))
+        })
+    }
+
+    fn is_near_resources(&self, point: Point2) -> bool {
+        self.units.resources.iter().any(|r| r.is_closer(3.0, point))
+            || self.start_location.distance(point) < 6.0
+    }
+}
+
+/// Returns the tiles covered by a square structure of `size` centered at `center`
+fn footprint(center: Point2, size: usize) -> impl Iterator<Item = (usize, usize)> {
+    let half = size as f32 / 2.0;
+    let (x0, y0) = (
+        (center.x - half).round() as usize,
+        (center.y - half).round() as usize,
+    );
+    (x0..x0 + size).flat_map(move |x| (y0..y0 + size).map(move |y| (x, y)))
+}
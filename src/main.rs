@@ -1,14 +1,42 @@
 mod army;
 mod base;
 mod bot;
+mod build_order;
+mod construction;
+mod detection;
+mod enemy;
+mod enemy_memory;
+mod exclusion;
+mod expansion;
+mod income;
+mod layout;
+mod mining;
+mod relocation;
+mod repair;
+mod research;
+mod reservation;
+mod scan;
+mod scouting;
+mod simulator;
+mod strategy;
+mod unit_data;
+mod validation;
+mod wall;
+mod worker_defense;
 
 use bot::TerranBot;
-use clap::Parser;
+use build_order::{BuildOrder, BuildOrderError, Matchups};
+use clap::{Parser, Subcommand, ValueEnum};
 use rust_sc2::prelude::*;
+use std::path::{Path, PathBuf};
+use validation::Issue;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// If the game should run in realtime or not
     #[arg(short, long)]
     realtime: bool,
@@ -16,11 +44,117 @@ struct Args {
     /// Difficulty level of the AI
     #[arg(short, long)]
     difficulty: Option<u8>,
+
+    /// Race of the AI
+    #[arg(long, value_enum, default_value_t = OpponentRace::Random)]
+    race: OpponentRace,
+
+    /// Directory with the build order for each matchup (tvt.toml, tvp.toml, tvz.toml and tvr.toml)
+    #[arg(long, default_value = "build_orders")]
+    build_orders: PathBuf,
+
+    /// Path to a build order file to play in every matchup instead
+    #[arg(short, long)]
+    build_order: Option<PathBuf>,
+
+    /// Seconds enemy units are remembered after they leave vision
+    #[arg(long, default_value_t = enemy_memory::DEFAULT_MEMORY_DECAY)]
+    enemy_memory: f32,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Simulate a build order offline and report its timings
+    Simulate {
+        /// Path to the build order file
+        path: PathBuf,
+
+        /// Race of the opponent, which selects the upgrade order and branches
+        #[arg(long, value_enum, default_value_t = OpponentRace::Random)]
+        race: OpponentRace,
+    },
+    /// Check build orders for steps which can never be started
+    Validate {
+        /// Paths to the build order files
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OpponentRace {
+    Terran,
+    Zerg,
+    Protoss,
+    Random,
+}
+
+impl From<OpponentRace> for Race {
+    fn from(race: OpponentRace) -> Self {
+        match race {
+            OpponentRace::Terran => Race::Terran,
+            OpponentRace::Zerg => Race::Zerg,
+            OpponentRace::Protoss => Race::Protoss,
+            OpponentRace::Random => Race::Random,
+        }
+    }
+}
+
+/// Loads a build order. Steps which can never be started are reported as warnings, and returned.
+fn load_build_order(path: &Path) -> Result<(BuildOrder, Vec<Issue>), BuildOrderError> {
+    let build_order = BuildOrder::load(path)?;
+    let issues = validation::validate(&build_order);
+    for issue in &issues {
+        eprintln!("Warning: {}: {}", path.display(), issue);
+    }
+    Ok((build_order, issues))
+}
+
+/// Loads a build order to play, exiting with an error message if it is invalid
+fn load_build_order_or_exit(path: &Path) -> BuildOrder {
+    load_build_order(path)
+        .unwrap_or_else(|e| {
+            report_invalid(path, &e);
+            std::process::exit(1);
+        })
+        .0
+}
+
+fn report_invalid(path: &Path, e: &BuildOrderError) {
+    eprintln!("Error: invalid build order {}: {}", path.display(), e);
 }
 
 fn main() -> SC2Result<()> {
     let args = Args::parse();
 
+    match &args.command {
+        Some(Command::Simulate { path, race }) => {
+            let build_order = load_build_order_or_exit(path);
+            println!("{}", simulator::simulate(&build_order, (*race).into()));
+            return Ok(());
+        }
+        Some(Command::Validate { paths }) => {
+            // Every build order is checked, so all of their problems are reported at once.
+            // `load_build_order` prints the issues of each build order.
+            let mut valid = true;
+            for path in paths {
+                match load_build_order(path) {
+                    Ok((_, issues)) => valid &= issues.is_empty(),
+                    Err(e) => {
+                        report_invalid(path, &e);
+                        valid = false;
+                    }
+                }
+            }
+            if !valid {
+                std::process::exit(1);
+            }
+            println!("All build orders are valid");
+            return Ok(());
+        }
+        None => {}
+    }
+
     let difficulty_level = if let Some(lvl) = args.difficulty {
         match lvl {
             0 => Difficulty::VeryEasy,
@@ -39,10 +173,21 @@ fn main() -> SC2Result<()> {
         Difficulty::Medium
     };
 
-    let mut bot = TerranBot::default();
+    let build_orders = if let Some(path) = &args.build_order {
+        Matchups::same(load_build_order_or_exit(path))
+    } else {
+        Matchups {
+            tvt: load_build_order_or_exit(&args.build_orders.join("tvt.toml")),
+            tvp: load_build_order_or_exit(&args.build_orders.join("tvp.toml")),
+            tvz: load_build_order_or_exit(&args.build_orders.join("tvz.toml")),
+            tvr: load_build_order_or_exit(&args.build_orders.join("tvr.toml")),
+        }
+    };
+
+    let mut bot = TerranBot::new(build_orders, args.enemy_memory);
     run_vs_computer(
         &mut bot,
-        Computer::new(Race::Random, difficulty_level, None),
+        Computer::new(args.race.into(), difficulty_level, None),
         "PortAleksanderLE",
         LaunchOptions {
             realtime: args.realtime,
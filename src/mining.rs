@@ -0,0 +1,266 @@
+use crate::bot::{Tag, TerranBot};
+use rust_sc2::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Mineral fields within this distance of a townhall are mined from it
+const MINERAL_FIELD_RADIUS: f32 = 8.0;
+/// Workers per patch before the base counts as saturated
+const WORKERS_PER_PATCH: usize = 2;
+/// Workers per patch at most, as more do not increase income
+const MAX_WORKERS_PER_PATCH: usize = 3;
+
+/// How many workers mine minerals at a base
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Saturation {
+    pub(crate) townhall: Tag,
+    pub(crate) workers: usize,
+    pub(crate) patches: usize,
+}
+
+impl Saturation {
+    /// Workers wanted at the base, two per patch
+    pub(crate) fn ideal(&self) -> usize {
+        self.patches * WORKERS_PER_PATCH
+    }
+
+    pub(crate) fn is_saturated(&self) -> bool {
+        self.workers >= self.ideal()
+    }
+}
+
+impl TerranBot {
+    /// Binds every mining worker to a mineral patch, filling every patch with two workers before
+    /// any gets a third. Workers of depleted patches or lost bases are moved to other patches.
+    /// `gas_workers` were sent to gas this step and are left alone.
+    pub(crate) fn assign_mineral_workers(&mut self, gas_workers: &[Tag]) {
+        let patches: FxHashSet<Tag> = self
+            .base_minerals()
+            .into_iter()
+            .flat_map(|(_, minerals)| minerals)
+            .collect();
+
+        // Forget workers which died or stopped mining, and patches which ran out
+        let mut assignments = std::mem::take(&mut self.mining_assignments);
+        assignments.retain(|&worker, &mut mineral| {
+            patches.contains(&mineral)
+                && !gas_workers.contains(&worker)
+                && !self.defending_workers.contains_key(&worker)
+                && !self.is_scout(worker)
+                && self
+                    .units
+                    .my
+                    .workers
+                    .get(worker)
+                    .is_some_and(|w| self.is_mining_minerals(w))
+        });
+        let mut counts: FxHashMap<Tag, usize> = patches.iter().map(|&p| (p, 0)).collect();
+        for mineral in assignments.values() {
+            *counts.entry(*mineral).or_default() += 1;
+        }
+
+        // Workers wander off to other patches when theirs is busy, send them back
+        for (&worker, &mineral) in &assignments {
+            if let Some(worker) = self.units.my.workers.get(worker) {
+                if worker.is_gathering() && worker.target_tag() != Some(mineral) {
+                    worker.gather(mineral, false);
+                }
+            }
+        }
+
+        // Move third workers to patches with fewer than two
+        let crowded: Vec<_> = assignments
+            .iter()
+            .filter(|(_, mineral)| counts[*mineral] > WORKERS_PER_PATCH)
+            .map(|(&worker, &mineral)| (worker, mineral))
+            .collect();
+        for (worker, mineral) in crowded {
+            if counts[&mineral] <= WORKERS_PER_PATCH {
+                continue;
+            }
+            let Some(worker) = self.units.my.workers.get(worker) else {
+                continue;
+            };
+            let patch = self
+                .closest_patch(worker, &counts, WORKERS_PER_PATCH)
+                .filter(|&patch| !self.is_transfer_threatened(mineral, patch));
+            if let Some(patch) = patch {
+                worker.gather(patch, false);
+                assignments.insert(worker.tag(), patch);
+                *counts.entry(mineral).or_default() -= 1;
+                *counts.entry(patch).or_default() += 1;
+            }
+        }
+
+        // Assign new and idle workers
+        let unassigned: Vec<_> = self
+            .units
+            .my
+            .workers
+            .iter()
+            .of_type(self.race_values.worker)
+            .filter(|w| {
+                !assignments.contains_key(&w.tag())
+                    && !gas_workers.contains(&w.tag())
+                    && !self.defending_workers.contains_key(&w.tag())
+                    && !self.is_scout(w.tag())
+            })
+            .filter(|w| {
+                (w.is_idle() && !self.is_reserved_builder(w.tag())) || self.is_mining_minerals(w)
+            })
+            .collect();
+        for worker in unassigned {
+            let patch = self
+                .closest_patch(worker, &counts, WORKERS_PER_PATCH)
+                .or_else(|| self.closest_patch(worker, &counts, MAX_WORKERS_PER_PATCH));
+            if let Some(patch) = patch {
+                worker.gather(patch, false);
+                assignments.insert(worker.tag(), patch);
+                *counts.entry(patch).or_default() += 1;
+            }
+        }
+
+        self.mining_assignments = assignments;
+    }
+
+    /// Moves the workers oversaturating other bases to the new base of `townhall` at once,
+    /// skipping bases from which the way there is threatened
+    pub(crate) fn transfer_workers(&mut self, townhall: Tag) {
+        let bases = self.base_minerals();
+        let Some((_, new_minerals)) = bases.iter().find(|(t, _)| *t == townhall) else {
+            return;
+        };
+        let mut counts: FxHashMap<Tag, usize> = new_minerals.iter().map(|&p| (p, 0)).collect();
+        for mineral in self.mining_assignments.values() {
+            if let Some(count) = counts.get_mut(mineral) {
+                *count += 1;
+            }
+        }
+
+        let mut transfers = Vec::new();
+        for saturation in self.saturations() {
+            let surplus = saturation.workers.saturating_sub(saturation.ideal());
+            if saturation.townhall == townhall || surplus == 0 {
+                continue;
+            }
+            let (Some(from), Some(to)) = (
+                self.units.my.townhalls.get(saturation.townhall),
+                self.units.my.townhalls.get(townhall),
+            ) else {
+                continue;
+            };
+            if self.is_path_threatened(from.position(), to.position()) {
+                println!("Not transferring workers, the way to the new base is threatened");
+                continue;
+            }
+
+            // Take workers of the most crowded patches first
+            let Some((_, minerals)) = bases.iter().find(|(t, _)| *t == saturation.townhall) else {
+                continue;
+            };
+            let mut workers: Vec<_> = self
+                .mining_assignments
+                .iter()
+                .filter(|(_, mineral)| minerals.contains(mineral))
+                .map(|(&worker, &mineral)| (worker, mineral))
+                .collect();
+            let crowding = |mineral: Tag| {
+                self.mining_assignments
+                    .values()
+                    .filter(|&&m| m == mineral)
+                    .count()
+            };
+            workers.sort_by_key(|&(_, mineral)| std::cmp::Reverse(crowding(mineral)));
+
+            for (worker, _) in workers.into_iter().take(surplus) {
+                let Some(unit) = self.units.my.workers.get(worker) else {
+                    continue;
+                };
+                let Some(patch) = self.closest_patch(unit, &counts, WORKERS_PER_PATCH) else {
+                    break;
+                };
+                unit.gather(patch, false);
+                *counts.entry(patch).or_default() += 1;
+                transfers.push((worker, patch));
+            }
+        }
+
+        if !transfers.is_empty() {
+            println!("Transferring {} workers to the new base", transfers.len());
+        }
+        self.mining_assignments.extend(transfers);
+    }
+
+    /// Returns true if the way between the bases of two mineral patches is threatened
+    fn is_transfer_threatened(&self, from: Tag, to: Tag) -> bool {
+        match (
+            self.units.mineral_fields.get(from),
+            self.units.mineral_fields.get(to),
+        ) {
+            (Some(from), Some(to)) => self.is_path_threatened(from.position(), to.position()),
+            _ => false,
+        }
+    }
+
+    /// Returns the mineral saturation of every ready townhall
+    pub(crate) fn saturations(&self) -> Vec<Saturation> {
+        self.base_minerals()
+            .into_iter()
+            .map(|(townhall, minerals)| Saturation {
+                townhall,
+                workers: self
+                    .mining_assignments
+                    .values()
+                    .filter(|m| minerals.contains(m))
+                    .count(),
+                patches: minerals.len(),
+            })
+            .collect()
+    }
+
+    /// Returns the mineral fields of each ready townhall
+    fn base_minerals(&self) -> Vec<(Tag, Vec<Tag>)> {
+        self.units
+            .my
+            .townhalls
+            .iter()
+            .ready()
+            .map(|townhall| {
+                let minerals = self
+                    .units
+                    .mineral_fields
+                    .iter()
+                    .closer(MINERAL_FIELD_RADIUS, townhall)
+                    .map(|m| m.tag())
+                    .collect();
+                (townhall.tag(), minerals)
+            })
+            .collect()
+    }
+
+    /// Returns the patch closest to the worker with fewer than `max` workers
+    fn closest_patch(
+        &self,
+        worker: &Unit,
+        counts: &FxHashMap<Tag, usize>,
+        max: usize,
+    ) -> Option<Tag> {
+        let free: Vec<_> = counts
+            .iter()
+            .filter(|&(_, &count)| count < max)
+            .map(|(&patch, _)| patch)
+            .collect();
+        self.units
+            .mineral_fields
+            .find_tags(&free)
+            .closest(worker)
+            .map(|m| m.tag())
+    }
+
+    fn is_mining_minerals(&self, worker: &Unit) -> bool {
+        (worker.is_gathering()
+            && worker
+                .target_tag()
+                .is_some_and(|t| self.units.mineral_fields.contains_tag(t)))
+            || (worker.is_returning() && worker.is_carrying_minerals())
+    }
+}
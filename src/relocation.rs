@@ -0,0 +1,201 @@
+use crate::{
+    bot::{Tag, TerranBot},
+    build_order::ExpansionStyle,
+    enemy::is_ground_threat,
+    unit_data::BURNING_HEALTH,
+};
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Townhalls below this health are lifted when attacked, before they start to burn
+const LIFT_HEALTH: f32 = 0.5;
+/// Enemies within this distance of a townhall or landing spot keep it in the air
+const LANDING_THREAT_RADIUS: f32 = 10.0;
+/// A townhall within this distance of an expansion location stands at that expansion. Expansion
+/// locations are computed from the resources around them, so a townhall placed or landed there
+/// can stand a little off, while one built beside it is at least a townhall's width away.
+const AT_EXPANSION_DISTANCE: f32 = 3.0;
+
+impl TerranBot {
+    /// Flies Command Centers built in the main to the best expansion, and lifts damaged
+    /// townhalls out of an attack to land them again once their base is safe
+    pub(crate) fn relocate_townhalls(&mut self) {
+        self.land_townhalls();
+        self.lift_attacked_townhalls();
+        self.lift_in_base_townhalls();
+    }
+
+    /// Returns true if the townhall was built away from any expansion and waits to be flown
+    pub(crate) fn is_in_base_townhall(&self, townhall: &Unit) -> bool {
+        self.build_order().expand != ExpansionStyle::InPlace
+            && matches!(townhall.type_id(), UID::CommandCenter | UID::OrbitalCommand)
+            && !self
+                .expansions
+                .iter()
+                .any(|e| e.loc.is_closer(AT_EXPANSION_DISTANCE, townhall))
+    }
+
+    fn lift_attacked_townhalls(&mut self) {
+        let attacked: Vec<_> = self
+            .units
+            .my
+            .townhalls
+            .iter()
+            .of_types(&vec![UID::CommandCenter, UID::OrbitalCommand])
+            .ready()
+            // Burning townhalls would burn down in the air, so they stay to be repaired
+            .filter(|t| {
+                t.health_percentage()
+                    .is_some_and(|h| (BURNING_HEALTH..LIFT_HEALTH).contains(&h))
+            })
+            .filter(|t| self.is_spot_threatened(t.position()))
+            .cloned()
+            .collect();
+        for townhall in attacked {
+            println!("Lifting {:?} out of an attack", townhall.type_id());
+            lift(&townhall);
+            // Land back at the same base once the attack is over
+            self.townhall_landings
+                .entry(townhall.tag())
+                .or_insert(townhall.position());
+        }
+    }
+
+    fn lift_in_base_townhalls(&mut self) {
+        if self.build_order().expand == ExpansionStyle::InPlace {
+            return;
+        }
+        let morph = self.build_order().expand == ExpansionStyle::InBaseOrbital;
+        let townhalls: Vec<_> = self
+            .units
+            .my
+            .townhalls
+            .iter()
+            .ready()
+            .idle()
+            .filter(|t| self.is_in_base_townhall(t))
+            .filter(|t| !self.townhall_landings.contains_key(&t.tag()))
+            .cloned()
+            .collect();
+        for townhall in townhalls {
+            if morph
+                && townhall.type_id() == UID::CommandCenter
+                && self.counter().count(UID::Barracks) > 0
+            {
+                if self.can_afford_unreserved(UID::OrbitalCommand, false) {
+                    townhall.use_ability(AbilityId::UpgradeToOrbitalOrbitalCommand, false);
+                    self.subtract_resources(UID::OrbitalCommand, false);
+                }
+                continue;
+            }
+            let Some(expansion) = self.next_landing_spot(townhall.tag()) else {
+                continue;
+            };
+            if self.is_path_threatened(townhall.position(), expansion)
+                || self.is_spot_threatened(expansion)
+            {
+                continue;
+            }
+            lift(&townhall);
+            self.townhall_landings.insert(townhall.tag(), expansion);
+        }
+    }
+
+    /// Lands flying townhalls at their expansion, or the best free one if theirs is taken
+    fn land_townhalls(&mut self) {
+        let mut landed = Vec::new();
+        for &tag in self.townhall_landings.keys() {
+            let Some(townhall) = self.units.my.structures.get(tag) else {
+                landed.push(tag);
+                continue;
+            };
+            // Idle on the ground means landed, or the lift failed and is tried again
+            if !townhall.is_flying() && townhall.is_idle() {
+                landed.push(tag);
+            }
+        }
+        for tag in landed {
+            self.townhall_landings.remove(&tag);
+            if self.units.my.townhalls.contains_tag(tag) {
+                self.transfer_workers(tag);
+            }
+        }
+
+        let flying: Vec<_> = self
+            .units
+            .my
+            .structures
+            .iter()
+            .of_types(&vec![UID::CommandCenterFlying, UID::OrbitalCommandFlying])
+            .cloned()
+            .collect();
+        for townhall in flying {
+            let target = self
+                .townhall_landings
+                .get(&townhall.tag())
+                .copied()
+                .filter(|&target| self.is_landing_spot_free(target, townhall.tag()))
+                .or_else(|| self.next_landing_spot(townhall.tag()));
+            let Some(target) = target else {
+                continue;
+            };
+            self.townhall_landings.insert(townhall.tag(), target);
+            if self.is_spot_threatened(target) {
+                continue;
+            }
+            let land = match townhall.type_id() {
+                UID::OrbitalCommandFlying => AbilityId::LandOrbitalCommand,
+                _ => AbilityId::LandCommandCenter,
+            };
+            if !townhall.orders().iter().any(|o| o.ability == land) {
+                townhall.command(land, Target::Pos(target), false);
+            }
+        }
+    }
+
+    /// Returns the best expansion no other townhall is landing at
+    fn next_landing_spot(&self, townhall: Tag) -> Option<Point2> {
+        self.expansion_ranking()
+            .into_iter()
+            .map(|e| e.location)
+            .find(|&spot| self.is_landing_spot_free(spot, townhall))
+    }
+
+    /// Returns true if no other townhall stands at or is landing on the spot
+    fn is_landing_spot_free(&self, spot: Point2, townhall: Tag) -> bool {
+        !self
+            .units
+            .my
+            .townhalls
+            .iter()
+            .any(|t| t.tag() != townhall && t.is_closer(AT_EXPANSION_DISTANCE, spot))
+            && !self
+                .units
+                .enemy
+                .structures
+                .iter()
+                .any(|s| s.is_closer(AT_EXPANSION_DISTANCE + 2.5, spot))
+            && !self
+                .townhall_landings
+                .iter()
+                .any(|(&t, &s)| t != townhall && s.distance(spot) < AT_EXPANSION_DISTANCE)
+    }
+
+    /// Returns true if an enemy which can attack ground units, other than a worker, is near
+    fn is_spot_threatened(&self, spot: Point2) -> bool {
+        self.units
+            .enemy
+            .units
+            .iter()
+            .filter(|u| is_ground_threat(u))
+            .any(|u| u.is_closer(LANDING_THREAT_RADIUS, spot))
+    }
+}
+
+fn lift(townhall: &Unit) {
+    let ability = match townhall.type_id() {
+        UID::OrbitalCommand => AbilityId::LiftOrbitalCommand,
+        _ => AbilityId::LiftCommandCenter,
+    };
+    townhall.use_ability(ability, false);
+}
@@ -0,0 +1,120 @@
+use crate::{
+    bot::{Tag, TerranBot},
+    unit_data::BURNING_HEALTH,
+};
+use rust_sc2::prelude::*;
+use rustc_hash::FxHashSet;
+use UnitTypeId as UID;
+
+/// Only SCVs within this distance of a target are sent to repair it
+const REPAIR_RANGE: f32 = 20.0;
+/// Mechanical units count as retreated when no enemy is within this distance
+const RETREAT_DISTANCE: f32 = 12.0;
+/// Unreserved minerals needed before anything but burning structures and bunkers is repaired
+const REPAIR_BUDGET: u32 = 100;
+
+impl TerranBot {
+    /// Sends nearby SCVs to repair damaged structures and mechanical units which have retreated,
+    /// up to a number of repairers per target
+    pub(crate) fn repair_damaged(&mut self) {
+        self.release_repairers();
+
+        let (minerals, _) = self.unreserved_resources();
+        let mut targets: Vec<_> = self
+            .units
+            .my
+            .structures
+            .iter()
+            .ready()
+            .filter(|s| s.health_percentage().is_some_and(|h| h < 1.0))
+            .chain(
+                self.units
+                    .my
+                    .units
+                    .iter()
+                    .filter(|u| u.is_mechanical() && !u.type_id().is_worker())
+                    .filter(|u| u.health_percentage().is_some_and(|h| h < 1.0))
+                    .filter(|u| self.units.enemy.all.closer(RETREAT_DISTANCE, *u).is_empty()),
+            )
+            .filter(|t| minerals > 0 && (is_critical(t) || minerals >= REPAIR_BUDGET))
+            .collect();
+        // Repair what would be lost first
+        targets.sort_by_key(|t| !is_critical(t));
+
+        let mut assigned = Vec::new();
+        let mut busy: FxHashSet<Tag> = self.repairers.keys().copied().collect();
+        for target in targets {
+            let repairing = self
+                .repairers
+                .values()
+                .chain(assigned.iter().map(|(_, t)| t))
+                .filter(|&&t| t == target.tag())
+                .count();
+            for _ in repairing..max_repairers(target) {
+                let Some(scv) = self.get_free_repairer(target.position(), &busy) else {
+                    break;
+                };
+                scv.repair(target.tag(), false);
+                busy.insert(scv.tag());
+                assigned.push((scv.tag(), target.tag()));
+            }
+        }
+        self.repairers.extend(assigned);
+    }
+
+    /// Forgets repairers whose target died or is repaired, so they go back to mining
+    fn release_repairers(&mut self) {
+        let released: Vec<_> = self
+            .repairers
+            .iter()
+            .filter(|&(&scv, &target)| {
+                self.units.my.workers.get(scv).is_none()
+                    || self
+                        .units
+                        .my
+                        .all
+                        .get(target)
+                        .is_none_or(|t| t.health_percentage().is_none_or(|h| h >= 1.0))
+            })
+            .map(|(&scv, _)| scv)
+            .collect();
+        for scv in released {
+            self.repairers.remove(&scv);
+            if let Some(scv) = self.units.my.workers.get(scv).filter(|s| s.is_repairing()) {
+                // Idle workers are given a mineral patch by `assign_mineral_workers`
+                scv.stop(false);
+            }
+        }
+    }
+
+    /// Like `get_closest_free_worker`, but only nearby SCVs which are not already repairing
+    fn get_free_repairer(&self, location: Point2, busy: &FxHashSet<Tag>) -> Option<&Unit> {
+        self.units
+            .my
+            .workers
+            .iter()
+            .of_type(UID::SCV)
+            .closer(REPAIR_RANGE, location)
+            .filter(|w| w.is_collecting() && !w.is_constructing() && !w.is_carrying_resource())
+            .filter(|w| !busy.contains(&w.tag()) && !self.defending_workers.contains_key(&w.tag()))
+            .closest(location)
+    }
+}
+
+/// Returns true if the target is lost without repairs: a burning structure or a bunker
+fn is_critical(target: &Unit) -> bool {
+    target.type_id() == UID::Bunker
+        || (target.is_structure()
+            && target
+                .health_percentage()
+                .is_some_and(|h| h < BURNING_HEALTH))
+}
+
+fn max_repairers(target: &Unit) -> usize {
+    match target.type_id() {
+        UID::Bunker | UID::PlanetaryFortress => 4,
+        _ if is_critical(target) => 3,
+        _ if target.is_structure() => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    bot::{format_time, BuildError, Tag, TerranBot},
+    unit_data::{upgrade_requirements, Requirement},
+};
+use rust_sc2::prelude::*;
+use rustc_hash::FxHashSet;
+
+/// Game seconds to wait for a research command to show up in the researcher's orders before
+/// the command is considered dropped and the upgrade is ordered again
+const RESEARCH_CONFIRM_TIMEOUT: f32 = 2.0;
+
+/// A research command which has not yet been confirmed by the researcher's orders
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingResearch {
+    pub(crate) upgrade: UpgradeId,
+    pub(crate) researcher: Tag,
+    pub(crate) ordered_at: f32,
+}
+
+impl TerranBot {
+    /// Keeps every idle researcher busy with the first upgrade of the upgrade order it can start.
+    ///
+    /// Upgrades which are blocked by a missing requirement or researcher are passed over, but if
+    /// the first startable upgrade cannot be afforded we save up for it instead of starting a
+    /// later one.
+    pub(crate) fn research_upgrades(&mut self) -> Result<(), BuildError> {
+        self.confirm_pending_research();
+
+        let mut busy: FxHashSet<Tag> = self.pending_research.iter().map(|p| p.researcher).collect();
+        let upgrades: Vec<_> = self
+            .build_order()
+            .upgrades
+            .iter()
+            .copied()
+            .filter(|&u| !self.is_research_started(u))
+            .collect();
+
+        let mut result = Ok(());
+        for upgrade in upgrades {
+            if let Err(e) = self.check_upgrade_requirements(upgrade) {
+                result = result.and(Err(e));
+                continue;
+            }
+            let Some(researcher) = self.find_idle_researcher(upgrade, &busy) else {
+                result = result.and(Err(BuildError::NoResearcher(upgrade)));
+                continue;
+            };
+            if !self.can_afford_upgrade_unreserved(upgrade) {
+                return result.and(Err(BuildError::CannotAffordUpgrade(upgrade)));
+            }
+
+            researcher.research(upgrade, false);
+            let researcher = researcher.tag();
+            busy.insert(researcher);
+            self.pending_research.push(PendingResearch {
+                upgrade,
+                researcher,
+                ordered_at: self.time,
+            });
+            self.subtract_upgrade_cost(upgrade);
+        }
+        result
+    }
+
+    /// Returns true if the upgrade is researched, being researched or ordered and awaiting
+    /// confirmation
+    fn is_research_started(&self, upgrade: UpgradeId) -> bool {
+        self.has_upgrade(upgrade)
+            || self.is_ordered_upgrade(upgrade)
+            || self.pending_research.iter().any(|p| p.upgrade == upgrade)
+    }
+
+    fn find_idle_researcher(&self, upgrade: UpgradeId, busy: &FxHashSet<Tag>) -> Option<&Unit> {
+        let researcher = *RESEARCHERS.get(&upgrade)?;
+        self.units
+            .my
+            .structures
+            .iter()
+            .of_type(researcher)
+            .idle()
+            .ready()
+            .filter(|r| !busy.contains(&r.tag()))
+            .closest(self.start_location)
+    }
+
+    /// Marks research as started once it shows up in the orders of its researcher, and forgets
+    /// commands which were dropped so the upgrade is ordered again
+    fn confirm_pending_research(&mut self) {
+        let mut started = Vec::new();
+        let mut dropped = Vec::new();
+        let pending = std::mem::take(&mut self.pending_research);
+        for research in pending {
+            let researcher = self.units.my.structures.get(research.researcher);
+            if self.has_upgrade(research.upgrade)
+                || researcher.is_some_and(|r| self.is_researching(r, research.upgrade))
+            {
+                started.push(research.upgrade);
+            } else if researcher.is_none()
+                || self.time - research.ordered_at > RESEARCH_CONFIRM_TIMEOUT
+            {
+                dropped.push(research.upgrade);
+            } else {
+                self.pending_research.push(research);
+            }
+        }
+
+        let time = format_time(self.time);
+        for upgrade in started {
+            self.log(&format!("{} {:?}: research started", time, upgrade));
+        }
+        for upgrade in dropped {
+            println!(
+                "{} {:?}: research did not start, ordering again",
+                time, upgrade
+            );
+        }
+    }
+
+    fn is_researching(&self, researcher: &Unit, upgrade: UpgradeId) -> bool {
+        self.game_data.upgrades.get(&upgrade).is_some_and(|data| {
+            researcher
+                .orders()
+                .iter()
+                .any(|order| order.ability == data.ability)
+        })
+    }
+
+    /// Returns an error if a structure or upgrade required for the research is missing
+    fn check_upgrade_requirements(&self, upgrade: UpgradeId) -> Result<(), BuildError> {
+        for requirement in upgrade_requirements(upgrade) {
+            match requirement {
+                Requirement::Structure(structure) if self.counter().count(structure) == 0 => {
+                    return Err(BuildError::UnfulfilledTechRequirement(structure));
+                }
+                Requirement::Upgrade(required) if !self.has_upgrade(required) => {
+                    return Err(BuildError::UnfulfilledUpgradeRequirement(required));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
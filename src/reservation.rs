@@ -0,0 +1,103 @@
+use crate::bot::{Tag, TerranBot};
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// What minerals and gas are held back for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reserver {
+    /// The build order step waiting for resources
+    NextStep,
+    /// A worker walking to a building site before the structure can be afforded
+    Builder(Tag),
+}
+
+/// Minerals and gas which only `holder` may spend
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Reservation {
+    pub(crate) holder: Reserver,
+    pub(crate) unit: UID,
+    pub(crate) minerals: u32,
+    pub(crate) vespene: u32,
+}
+
+impl TerranBot {
+    /// Holds back the cost of `unit` for `holder`, replacing what it held before
+    pub(crate) fn reserve(&mut self, holder: Reserver, unit: UID) {
+        self.release(holder);
+        let cost = self.get_unit_cost(unit);
+        self.reservations.push(Reservation {
+            holder,
+            unit,
+            minerals: cost.minerals,
+            vespene: cost.vespene,
+        });
+    }
+
+    pub(crate) fn release(&mut self, holder: Reserver) {
+        self.reservations.retain(|r| r.holder != holder);
+    }
+
+    /// Releases the resources of builders which died, or which walk to build something that is
+    /// no longer the next step
+    pub(crate) fn release_stale_reservations(&mut self, next: Option<UID>) {
+        let stale: Vec<_> = self
+            .reservations
+            .iter()
+            .filter(|r| match r.holder {
+                Reserver::NextStep => false,
+                Reserver::Builder(tag) => {
+                    self.units.my.workers.get(tag).is_none() || Some(r.unit) != next
+                }
+            })
+            .map(|r| r.holder)
+            .collect();
+        for holder in stale {
+            self.release(holder);
+        }
+    }
+
+    /// Returns the worker already walking to the site of `structure`, if any
+    pub(crate) fn reserved_builder(&self, structure: UID) -> Option<&Unit> {
+        self.reservations
+            .iter()
+            .find_map(|r| match r.holder {
+                Reserver::Builder(tag) if r.unit == structure => Some(tag),
+                _ => None,
+            })
+            .and_then(|tag| self.units.my.workers.get(tag))
+    }
+
+    /// Returns true if the worker is walking to a building site
+    pub(crate) fn is_reserved_builder(&self, worker: Tag) -> bool {
+        self.reservations
+            .iter()
+            .any(|r| r.holder == Reserver::Builder(worker))
+    }
+
+    /// Returns the minerals and gas which are not reserved for the build order
+    pub(crate) fn unreserved_resources(&self) -> (u32, u32) {
+        let (minerals, vespene) = self
+            .reservations
+            .iter()
+            .fold((0, 0), |(m, v), r| (m + r.minerals, v + r.vespene));
+        (
+            self.minerals.saturating_sub(minerals),
+            self.vespene.saturating_sub(vespene),
+        )
+    }
+
+    /// Like `can_afford`, but without spending minerals and gas reserved for the build order
+    pub(crate) fn can_afford_unreserved(&self, unit: UID, check_supply: bool) -> bool {
+        let cost = self.get_unit_cost(unit);
+        let (minerals, vespene) = self.unreserved_resources();
+        self.can_afford(unit, check_supply) && minerals >= cost.minerals && vespene >= cost.vespene
+    }
+
+    /// Like `can_afford_upgrade`, but without spending minerals and gas reserved for the build
+    /// order
+    pub(crate) fn can_afford_upgrade_unreserved(&self, upgrade: UpgradeId) -> bool {
+        let cost = self.get_upgrade_cost(upgrade);
+        let (minerals, vespene) = self.unreserved_resources();
+        minerals >= cost.minerals && vespene >= cost.vespene
+    }
+}
@@ -0,0 +1,199 @@
+use crate::bot::{Tag, TerranBot};
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Energy a Scanner Sweep or a MULE costs
+const ORBITAL_ABILITY_ENERGY: u32 = 50;
+/// Radius a Scanner Sweep reveals
+const SCAN_RADIUS: f32 = 13.0;
+/// Game seconds a Scanner Sweep lasts
+const SCAN_DURATION: f32 = 12.3;
+/// Cloaked enemies are scanned when they are this close to our units or structures
+const CLOAKED_THREAT_RADIUS: f32 = 10.0;
+/// Remembered enemies in the fog are scanned when this many of our army units are this close
+const FOG_ENGAGE_RADIUS: f32 = 15.0;
+const FOG_ENGAGE_ARMY: usize = 6;
+/// Scans kept for cloaked units unless the build order sets its own reserve
+pub(crate) const DEFAULT_SCAN_RESERVE: u32 = 1;
+
+/// What the Orbital Commands have scanned
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Scanner {
+    /// Where scans are active, and when each ends
+    pub(crate) active: Vec<(Point2, f32)>,
+    /// Tech scans of the build order already made
+    pub(crate) tech_scans: usize,
+}
+
+impl TerranBot {
+    /// Spends Orbital Command energy: scans cloaked enemies attacking us, enemies in the fog the
+    /// army is about to fight and the enemy main at the build order's tech scan times, then
+    /// calls down MULEs with the energy above the scan reserve
+    pub(crate) fn process_orbitals(&mut self) {
+        let time = self.time;
+        self.scanner.active.retain(|&(_, end)| end > time);
+        if self.is_tech_scan_due() && self.is_visible(self.enemy_start) {
+            // Something else sees their main, no need to scan it
+            self.scanner.tech_scans += 1;
+        }
+
+        let mut orbitals: Vec<_> = self
+            .units
+            .my
+            .townhalls
+            .iter()
+            .of_type(UID::OrbitalCommand)
+            .ready()
+            .filter(|o| o.energy().unwrap_or(0) >= ORBITAL_ABILITY_ENERGY)
+            .map(|o| (o.tag(), o.energy().unwrap_or(0)))
+            .collect();
+        // Use the fullest Orbital Command first
+        orbitals.sort_by_key(|&(_, energy)| std::cmp::Reverse(energy));
+        let mut energy: u32 = orbitals.iter().map(|&(_, energy)| energy).sum();
+        let mut used = Vec::new();
+
+        // Cloaked units may use every scan, others leave the build order's reserve for them.
+        // Attacks by unseen enemies may come from the fog, so they only use the reserve once
+        // cloaked units are expected.
+        let reserve = self.build_order().scan_reserve * ORBITAL_ABILITY_ENERGY;
+        let unseen_keep = if self.cloak_noticed_at.is_some() {
+            0
+        } else {
+            reserve
+        };
+        let scans = self
+            .cloaked_scan_target()
+            .map(|target| (target, 0))
+            .into_iter()
+            .chain(
+                self.unseen_attack_positions()
+                    .first()
+                    .map(|&t| (t, unseen_keep)),
+            )
+            .chain(self.fog_scan_target().map(|t| (t, reserve)))
+            .chain(self.tech_scan_target().map(|t| (t, reserve)));
+        for (target, keep) in scans.collect::<Vec<_>>() {
+            if energy < ORBITAL_ABILITY_ENERGY + keep || self.is_scanned(target) {
+                continue;
+            }
+            let Some(&(orbital, _)) = orbitals.iter().find(|(tag, _)| !used.contains(tag)) else {
+                break;
+            };
+            if let Some(orbital) = self.units.my.townhalls.get(orbital) {
+                orbital.command(AbilityId::ScannerSweepScan, Target::Pos(target), false);
+            }
+            used.push(orbital);
+            energy -= ORBITAL_ABILITY_ENERGY;
+            self.scanner.active.push((target, time + SCAN_DURATION));
+            if self.is_tech_scan_due() && target == self.enemy_start {
+                self.scanner.tech_scans += 1;
+            }
+        }
+
+        self.call_down_mules(&used, energy.saturating_sub(reserve));
+    }
+
+    /// Returns true if an active scan already reveals the point
+    fn is_scanned(&self, point: Point2) -> bool {
+        self.scanner
+            .active
+            .iter()
+            .any(|(scan, _)| scan.distance(point) < SCAN_RADIUS)
+    }
+
+    /// Returns the position of an undetected cloaked or burrowed enemy close to us, which we
+    /// have units around to kill
+    fn cloaked_scan_target(&self) -> Option<Point2> {
+        self.units
+            .enemy
+            .all
+            .iter()
+            .filter(|e| (e.is_cloaked() || e.is_burrowed()) && !e.is_revealed())
+            .filter(|e| {
+                self.units
+                    .my
+                    .all
+                    .iter()
+                    .any(|u| u.is_closer(CLOAKED_THREAT_RADIUS, *e))
+                    && self
+                        .units
+                        .my
+                        .units
+                        .iter()
+                        .any(|u| u.can_attack() && u.is_closer(CLOAKED_THREAT_RADIUS, *e))
+            })
+            .map(|e| e.position())
+            .find(|&position| !self.is_detected(position))
+    }
+
+    /// Returns the position of remembered enemies out of vision which the army is closing in on
+    fn fog_scan_target(&self) -> Option<Point2> {
+        let army: Vec<_> = self
+            .units
+            .my
+            .units
+            .iter()
+            .filter(|u| !u.type_id().is_worker() && u.can_attack())
+            .collect();
+        self.enemy_memory
+            .units()
+            .iter()
+            .filter(|e| !self.units.enemy.all.contains_tag(e.tag()))
+            .map(|e| e.position())
+            .find(|&position| {
+                !self.is_visible(position)
+                    && army
+                        .iter()
+                        .filter(|u| u.is_closer(FOG_ENGAGE_RADIUS, position))
+                        .count()
+                        >= FOG_ENGAGE_ARMY
+            })
+    }
+
+    /// Returns the enemy main if a tech scan of the build order is due and we do not see it
+    fn tech_scan_target(&self) -> Option<Point2> {
+        (self.is_tech_scan_due() && !self.is_visible(self.enemy_start)).then_some(self.enemy_start)
+    }
+
+    fn is_tech_scan_due(&self) -> bool {
+        self.build_order()
+            .tech_scans
+            .get(self.scanner.tech_scans)
+            .is_some_and(|&time| self.time >= time)
+    }
+
+    /// Calls down MULEs at unsaturated bases with up to `spare` energy, from Orbital Commands
+    /// which did not scan
+    fn call_down_mules(&self, scanned: &[Tag], mut spare: u32) {
+        let unsaturated: Vec<_> = self
+            .saturations()
+            .iter()
+            .filter(|s| !s.is_saturated())
+            .map(|s| s.townhall)
+            .collect();
+        for orbital in self.units.my.townhalls.iter().of_type(UID::OrbitalCommand) {
+            if spare < ORBITAL_ABILITY_ENERGY
+                || scanned.contains(&orbital.tag())
+                || !orbital.has_ability(AbilityId::CalldownMULECalldownMULE)
+            {
+                continue;
+            }
+            if let Some(townhall) = self
+                .units
+                .my
+                .townhalls
+                .find_tags(&unsaturated)
+                .closest(orbital.position())
+            {
+                if let Some(mineral) = self.units.mineral_fields.closest(townhall.position()) {
+                    orbital.command(
+                        AbilityId::CalldownMULECalldownMULE,
+                        Target::Tag(mineral.tag()),
+                        false,
+                    );
+                    spare -= ORBITAL_ABILITY_ENERGY;
+                }
+            }
+        }
+    }
+}
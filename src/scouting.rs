@@ -0,0 +1,160 @@
+use crate::{
+    bot::{format_time, Tag, TerranBot},
+    enemy::is_ground_threat,
+};
+use rust_sc2::prelude::*;
+use std::{collections::VecDeque, f32::consts::TAU};
+
+/// Distance from the enemy start location at which the scout circles their main
+const SCOUT_CIRCLE_RADIUS: f32 = 12.0;
+/// Points on the circle around the enemy main
+const SCOUT_CIRCLE_POINTS: usize = 8;
+/// A waypoint counts as visited within this distance
+const WAYPOINT_REACHED: f32 = 3.0;
+/// Waypoints with a known enemy which can attack ground units this close are skipped
+const SCOUT_THREAT_RADIUS: f32 = 7.0;
+/// The scout turns home below this health, or when an enemy other than a worker chases it.
+/// Workers chasing it are outrun, so it keeps going.
+const SCOUT_RETREAT_HEALTH: f32 = 0.4;
+
+/// The SCV scouting the enemy and the way it takes
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Scouting {
+    /// The SCV scouting, `None` before it is sent and once it is back
+    pub(crate) scout: Option<Tag>,
+    /// Points still to visit, in order
+    pub(crate) route: VecDeque<Point2>,
+    /// True once a scout has been sent, only one is sent per game
+    pub(crate) sent: bool,
+}
+
+impl TerranBot {
+    /// Sends an SCV to scout once the build order's scout trigger holds, and walks it around the
+    /// enemy main and past their natural. What it sees is recorded like anything else we see, in
+    /// `enemies_seen` and `enemy_memory`.
+    pub(crate) fn scout(&mut self) {
+        if !self.scouting.sent {
+            self.send_scout();
+            return;
+        }
+        let Some(tag) = self.scouting.scout else {
+            return;
+        };
+        let Some(scout) = self.units.my.workers.get(tag).cloned() else {
+            println!("Scout lost");
+            self.scouting.scout = None;
+            return;
+        };
+
+        if self.is_scout_chased(&scout) && !self.scouting.route.is_empty() {
+            println!("Scout chased, returning home");
+            self.scouting.route.clear();
+        }
+        // Skip visited waypoints, and those a known enemy guards
+        while let Some(&waypoint) = self.scouting.route.front() {
+            if scout.is_closer(WAYPOINT_REACHED, waypoint) || self.is_waypoint_threatened(waypoint)
+            {
+                self.scouting.route.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let destination = match self.scouting.route.front() {
+            Some(&waypoint) => waypoint,
+            None if scout.is_closer(SCOUT_CIRCLE_RADIUS, self.start_location) => {
+                // Back home, `assign_mineral_workers` gives it a mineral patch
+                scout.stop(false);
+                self.scouting.scout = None;
+                return;
+            }
+            None => self.start_location,
+        };
+        if scout.target_pos() != Some(destination) {
+            scout.move_to(Target::Pos(destination), false);
+        }
+    }
+
+    /// Returns true if the worker is scouting
+    pub(crate) fn is_scout(&self, worker: Tag) -> bool {
+        self.scouting.scout == Some(worker)
+    }
+
+    fn send_scout(&mut self) {
+        if self
+            .build_order()
+            .scout
+            .is_none_or(|trigger| !self.trigger_holds(trigger))
+        {
+            return;
+        }
+        let Some(scout) = self
+            .units
+            .my
+            .workers
+            .iter()
+            .filter(|w| w.is_collecting() && !w.is_constructing() && !w.is_carrying_resource())
+            .filter(|w| !self.is_reserved_builder(w.tag()))
+            .closest(self.enemy_start)
+            .map(|w| w.tag())
+        else {
+            return;
+        };
+        println!("{} Sending scout", format_time(self.time));
+        self.mining_assignments.remove(&scout);
+        self.scouting = Scouting {
+            scout: Some(scout),
+            route: self.scouting_route(),
+            sent: true,
+        };
+    }
+
+    /// Returns points circling the enemy main, starting from the side facing us, followed by
+    /// the enemy natural
+    fn scouting_route(&self) -> VecDeque<Point2> {
+        let center = self.enemy_start;
+        let height = self.get_height((center.x as usize, center.y as usize));
+        let towards_us = self.start_location - center;
+        let start_angle = towards_us.y.atan2(towards_us.x);
+        let mut route: VecDeque<_> = (0..SCOUT_CIRCLE_POINTS)
+            .map(|i| {
+                let angle = start_angle + TAU * i as f32 / SCOUT_CIRCLE_POINTS as f32;
+                center.offset(
+                    SCOUT_CIRCLE_RADIUS * angle.cos(),
+                    SCOUT_CIRCLE_RADIUS * angle.sin(),
+                )
+            })
+            // Stay in their main, where the scout can walk
+            .filter(|p| {
+                let tile = (p.x as usize, p.y as usize);
+                self.is_pathable(tile) && self.get_height(tile) == height
+            })
+            .collect();
+        route.extend(self.enemy_natural());
+        route
+    }
+
+    /// Returns true if the scout is hurt, or an enemy which is not a worker is after it
+    fn is_scout_chased(&self, scout: &Unit) -> bool {
+        scout
+            .health_percentage()
+            .is_some_and(|h| h < SCOUT_RETREAT_HEALTH)
+            || self
+                .units
+                .enemy
+                .units
+                .iter()
+                .filter(|u| is_ground_threat(u))
+                .any(|u| u.is_closer(u.ground_range() + 2.0, scout))
+    }
+
+    /// Returns true if a remembered enemy which can attack ground units, other than a worker,
+    /// is close to the waypoint
+    fn is_waypoint_threatened(&self, waypoint: Point2) -> bool {
+        self.enemy_memory
+            .all()
+            .iter()
+            .filter(|u| is_ground_threat(u))
+            .any(|u| u.is_closer(SCOUT_THREAT_RADIUS, waypoint))
+    }
+}
@@ -0,0 +1,654 @@
+use crate::{
+    base::{END_OF_BUILD_PRIO, GAS_INCOME_PER_WORKER, MINERAL_INCOME_PER_WORKER},
+    bot::{format_time, BuildError},
+    build_order::{producer_of, BuildOrder, BuildStep, Condition, Trigger},
+    unit_data::{
+        required_addon, supply_provided, unit_cost, upgrade_cost, upgrade_requirements, BuildCost,
+        Requirement,
+    },
+};
+use rust_sc2::prelude::*;
+use std::fmt;
+use UnitTypeId as UID;
+
+/// Game seconds between each update of the simulation
+const TIME_STEP: f32 = 0.25;
+/// The simulation stops after this many game seconds
+const TIME_LIMIT: f32 = 900.0;
+/// Minerals above this amount count as floating
+const FLOAT_THRESHOLD: f32 = 300.0;
+const WORKERS_PER_BASE: usize = 16;
+const WORKERS_PER_GAS: usize = 3;
+const MAX_WORKERS: usize = 80;
+
+#[derive(Debug)]
+pub(crate) struct StepReport {
+    pub(crate) unit: UID,
+    pub(crate) start: f32,
+    pub(crate) finish: f32,
+}
+
+#[derive(Debug)]
+pub(crate) struct UpgradeReport {
+    pub(crate) upgrade: UpgradeId,
+    pub(crate) start: f32,
+    pub(crate) finish: f32,
+}
+
+/// The result of simulating a build order
+#[derive(Debug)]
+pub(crate) struct SimReport {
+    pub(crate) name: String,
+    pub(crate) race: Race,
+    pub(crate) steps: Vec<StepReport>,
+    pub(crate) upgrades: Vec<UpgradeReport>,
+    /// Start and end time of each period where production waited for supply
+    pub(crate) supply_blocks: Vec<(f32, f32)>,
+    pub(crate) average_minerals: f32,
+    pub(crate) average_vespene: f32,
+    /// Game seconds spent with more than `FLOAT_THRESHOLD` minerals banked
+    pub(crate) time_floating: f32,
+    /// The step the build order got stuck on, and the reason it could not be started
+    pub(crate) stalled_step: Option<(usize, UID, BuildError)>,
+    /// The upgrade the upgrade order got stuck on, and the reason it could not be started
+    pub(crate) stalled_upgrade: Option<(UpgradeId, BuildError)>,
+    pub(crate) end_time: f32,
+}
+
+/// Plays a build order and its upgrade order against a model of income, build times,
+/// supply and tech requirements, without launching the game.
+///
+/// Enemy units cannot be seen, so only branches on the enemy race are ever taken. Workers are
+/// trained continuously like `train_workers` does, but no army is trained after the build order.
+pub(crate) fn simulate(build_order: &BuildOrder, race: Race) -> SimReport {
+    let steps = build_order.active_steps(|condition| match *condition {
+        Condition::EnemyRace(enemy_race) => enemy_race == race,
+        Condition::EnemySeen { .. } | Condition::EnemyStrategy(_) => false,
+    });
+    let mut simulation = Simulation::new(steps, build_order.upgrades.clone());
+    simulation.run();
+    simulation.into_report(build_order.name.clone(), race)
+}
+
+struct Structure {
+    unit: UID,
+    /// Game time when the structure is finished
+    ready_at: f32,
+    /// Game time when each production slot is free. The second slot requires a reactor.
+    busy_until: [f32; 2],
+    addon: Option<UID>,
+}
+
+impl Structure {
+    fn new(unit: UID, ready_at: f32) -> Self {
+        Self {
+            unit,
+            ready_at,
+            busy_until: [ready_at; 2],
+            addon: None,
+        }
+    }
+
+    fn is_ready(&self, time: f32) -> bool {
+        self.ready_at <= time
+    }
+
+    fn is_townhall(&self) -> bool {
+        matches!(
+            self.unit,
+            UID::CommandCenter | UID::OrbitalCommand | UID::PlanetaryFortress
+        )
+    }
+
+    /// Returns true if the structure counts as `unit`, e.g. an Orbital Command as a Command Center
+    fn is(&self, unit: UID) -> bool {
+        self.unit == unit || (unit == UID::CommandCenter && self.is_townhall())
+    }
+
+    fn free_slot(&self, time: f32) -> Option<usize> {
+        let slots = if matches!(
+            self.addon,
+            Some(UID::BarracksReactor | UID::FactoryReactor | UID::StarportReactor)
+        ) {
+            2
+        } else {
+            1
+        };
+        if !self.is_ready(time) {
+            return None;
+        }
+        (0..slots).find(|&i| self.busy_until[i] <= time)
+    }
+
+    fn is_idle(&self, time: f32) -> bool {
+        self.is_ready(time) && self.busy_until.iter().all(|&t| t <= time)
+    }
+}
+
+enum Completion {
+    Worker,
+    Morph(usize, UID),
+}
+
+struct Simulation {
+    steps: Vec<BuildStep>,
+    upgrades: Vec<UpgradeId>,
+    time: f32,
+    minerals: f32,
+    vespene: f32,
+    supply_used: u32,
+    /// Finished workers, including those busy building
+    workers: usize,
+    /// Game time when each worker which has been sent to build is free again
+    builders: Vec<f32>,
+    structures: Vec<Structure>,
+    pending: Vec<(f32, Completion)>,
+    step_reports: Vec<StepReport>,
+    upgrade_reports: Vec<UpgradeReport>,
+    step_error: Option<BuildError>,
+    /// The first upgrade which could not be started in the last step, and why
+    upgrade_error: Option<(UpgradeId, BuildError)>,
+    supply_blocks: Vec<(f32, f32)>,
+    supply_blocked_since: Option<f32>,
+    mineral_sum: f32,
+    vespene_sum: f32,
+    time_floating: f32,
+}
+
+impl Simulation {
+    fn new(steps: Vec<BuildStep>, upgrades: Vec<UpgradeId>) -> Self {
+        Self {
+            steps,
+            upgrades,
+            time: 0.0,
+            minerals: 50.0,
+            vespene: 0.0,
+            supply_used: 12,
+            workers: 12,
+            builders: Vec::new(),
+            structures: vec![Structure::new(UID::CommandCenter, 0.0)],
+            pending: Vec::new(),
+            step_reports: Vec::new(),
+            upgrade_reports: Vec::new(),
+            step_error: None,
+            upgrade_error: None,
+            supply_blocks: Vec::new(),
+            supply_blocked_since: None,
+            mineral_sum: 0.0,
+            vespene_sum: 0.0,
+            time_floating: 0.0,
+        }
+    }
+
+    fn run(&mut self) {
+        while self.time < TIME_LIMIT && !self.is_done() {
+            self.complete_finished();
+
+            let mut supply_blocked = false;
+            if let Some(&step) = self.steps.get(self.step_reports.len()) {
+                match self.start_step(step) {
+                    Ok(finish) => {
+                        self.step_reports.push(StepReport {
+                            unit: step.unit,
+                            start: self.time,
+                            finish,
+                        });
+                        self.step_error = None;
+                    }
+                    Err(e) => {
+                        supply_blocked |= matches!(e, BuildError::SupplyBlocked(_));
+                        self.step_error = Some(e);
+                    }
+                }
+            }
+            self.start_upgrades();
+            supply_blocked |= self.train_workers();
+            self.build_supply();
+            self.track_supply_block(supply_blocked);
+            self.gather();
+
+            self.time += TIME_STEP;
+        }
+        if let Some(since) = self.supply_blocked_since.take() {
+            self.supply_blocks.push((since, self.time));
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.step_reports.len() == self.steps.len()
+            && self.upgrade_reports.len() == self.upgrades.len()
+            && self.pending.is_empty()
+            && self.step_reports.iter().all(|r| r.finish <= self.time)
+            && self.upgrade_reports.iter().all(|r| r.finish <= self.time)
+    }
+
+    fn complete_finished(&mut self) {
+        let time = self.time;
+        let (finished, pending): (Vec<_>, Vec<_>) =
+            self.pending.drain(..).partition(|(t, _)| *t <= time);
+        self.pending = pending;
+        for (_, completion) in finished {
+            match completion {
+                Completion::Worker => self.workers += 1,
+                Completion::Morph(index, unit) => self.structures[index].unit = unit,
+            }
+        }
+    }
+
+    fn supply_cap(&self) -> u32 {
+        self.structures
+            .iter()
+            .filter(|s| s.is_ready(self.time))
+            .map(|s| supply_provided(s.unit))
+            .sum::<u32>()
+            .min(200)
+    }
+
+    fn count(&self, unit: UID) -> usize {
+        self.structures.iter().filter(|s| s.is(unit)).count()
+    }
+
+    fn has_ready(&self, unit: UID) -> bool {
+        self.structures
+            .iter()
+            .any(|s| s.is(unit) && s.is_ready(self.time))
+    }
+
+    /// Workers which are not busy building
+    fn mining_workers(&self) -> usize {
+        let building = self.builders.iter().filter(|&&t| t > self.time).count();
+        self.workers.saturating_sub(building)
+    }
+
+    fn has_upgrade(&self, upgrade: UpgradeId) -> bool {
+        self.upgrade_reports
+            .iter()
+            .any(|r| r.upgrade == upgrade && r.finish <= self.time)
+    }
+
+    fn can_afford(&self, cost: BuildCost) -> bool {
+        self.minerals >= cost.minerals as f32 && self.vespene >= cost.vespene as f32
+    }
+
+    fn spend(&mut self, cost: BuildCost) {
+        self.minerals -= cost.minerals as f32;
+        self.vespene -= cost.vespene as f32;
+        self.supply_used += cost.supply;
+    }
+
+    fn trigger_holds(&self, trigger: Trigger) -> bool {
+        match trigger {
+            Trigger::Supply(supply) => self.supply_used >= supply,
+            Trigger::Time(time) => self.time >= time,
+            Trigger::Progress(structure, progress) => {
+                self.structures.iter().filter(|s| s.is(structure)).any(|s| {
+                    let build_time = unit_cost(s.unit).map_or(1.0, |c| c.time);
+                    1.0 - (s.ready_at - self.time).max(0.0) / build_time >= progress
+                })
+            }
+        }
+    }
+
+    /// Starts the step if possible, returning the time it will be finished
+    fn start_step(&mut self, step: BuildStep) -> Result<f32, BuildError> {
+        let unit = step.unit;
+        if step.trigger.is_some_and(|t| !self.trigger_holds(t)) {
+            return Err(BuildError::WaitingForTrigger(unit));
+        }
+        let cost = unit_cost(unit).ok_or(BuildError::InvalidArgument(unit))?;
+        if TECH_REQUIREMENTS
+            .get(&unit)
+            .is_some_and(|&requirement| !self.has_ready(requirement))
+        {
+            return Err(BuildError::UnfulfilledTechRequirement(unit));
+        } else if !self.can_afford(cost) {
+            return Err(BuildError::CannotAfford(unit));
+        } else if self.supply_used + cost.supply > self.supply_cap() {
+            return Err(BuildError::SupplyBlocked(unit));
+        }
+
+        let time = self.time;
+        let finish = time + cost.time;
+        match producer_of(unit).ok_or(BuildError::NoProducer(unit))? {
+            UID::SCV => {
+                if unit == UID::Refinery
+                    && self.count(UID::Refinery) >= 2 * self.count(UID::CommandCenter)
+                {
+                    return Err(BuildError::NoSuitableLocation(unit));
+                } else if self.mining_workers() == 0 {
+                    return Err(BuildError::NoSuitableWorker);
+                }
+                self.builders.push(finish);
+                self.structures.push(Structure::new(unit, finish));
+            }
+            UID::CommandCenter if unit == UID::SCV => {
+                let townhall = self
+                    .structures
+                    .iter_mut()
+                    .find(|s| s.is_townhall() && s.free_slot(time).is_some())
+                    .ok_or(BuildError::NoProducer(unit))?;
+                townhall.busy_until[0] = finish;
+                self.pending.push((finish, Completion::Worker));
+            }
+            UID::CommandCenter => {
+                let index = self
+                    .structures
+                    .iter()
+                    .position(|s| s.unit == UID::CommandCenter && s.is_idle(time))
+                    .ok_or(BuildError::NoProducer(unit))?;
+                self.structures[index].busy_until[0] = finish;
+                self.pending.push((finish, Completion::Morph(index, unit)));
+            }
+            producer if unit.is_addon() => {
+                let parent = self
+                    .structures
+                    .iter_mut()
+                    .find(|s| s.unit == producer && s.addon.is_none() && s.is_idle(time))
+                    .ok_or(BuildError::NoProducer(unit))?;
+                parent.busy_until = [finish; 2];
+                parent.addon = Some(unit);
+                self.structures.push(Structure::new(unit, finish));
+            }
+            producer => {
+                let addon = required_addon(unit);
+                let (parent, slot) = self
+                    .structures
+                    .iter_mut()
+                    .filter(|s| s.unit == producer && (addon.is_none() || s.addon == addon))
+                    .find_map(|s| s.free_slot(time).map(|slot| (s, slot)))
+                    .ok_or(BuildError::NoProducer(unit))?;
+                parent.busy_until[slot] = finish;
+            }
+        }
+        self.spend(cost);
+        Ok(finish)
+    }
+
+    /// Starts the first startable upgrades on every idle researcher like `research_upgrades`,
+    /// saving up if the first startable upgrade cannot be afforded
+    fn start_upgrades(&mut self) {
+        self.upgrade_error = None;
+        let upgrades: Vec<_> = self
+            .upgrades
+            .iter()
+            .copied()
+            .filter(|&u| !self.upgrade_reports.iter().any(|r| r.upgrade == u))
+            .collect();
+        for upgrade in upgrades {
+            match self.start_upgrade(upgrade) {
+                Ok(finish) => self.upgrade_reports.push(UpgradeReport {
+                    upgrade,
+                    start: self.time,
+                    finish,
+                }),
+                Err(e) => {
+                    let saving = matches!(e, BuildError::CannotAffordUpgrade(_));
+                    self.upgrade_error.get_or_insert((upgrade, e));
+                    if saving {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts researching the upgrade if possible, returning the time it will be finished
+    fn start_upgrade(&mut self, upgrade: UpgradeId) -> Result<f32, BuildError> {
+        let cost = upgrade_cost(upgrade).ok_or(BuildError::NoResearcher(upgrade))?;
+        for requirement in upgrade_requirements(upgrade) {
+            match requirement {
+                Requirement::Structure(structure) if !self.has_ready(structure) => {
+                    return Err(BuildError::UnfulfilledTechRequirement(structure));
+                }
+                Requirement::Upgrade(required) if !self.has_upgrade(required) => {
+                    return Err(BuildError::UnfulfilledUpgradeRequirement(required));
+                }
+                _ => {}
+            }
+        }
+        let researcher = *RESEARCHERS
+            .get(&upgrade)
+            .ok_or(BuildError::NoResearcher(upgrade))?;
+        let time = self.time;
+        let finish = time + cost.time;
+        let index = self
+            .structures
+            .iter()
+            .position(|s| s.unit == researcher && s.is_idle(time))
+            .ok_or(BuildError::NoResearcher(upgrade))?;
+        if !self.can_afford(cost) {
+            return Err(BuildError::CannotAffordUpgrade(upgrade));
+        }
+        self.structures[index].busy_until[0] = finish;
+        self.spend(cost);
+        Ok(finish)
+    }
+
+    /// Trains workers from idle townhalls like `train_workers`, returning true if supply blocked
+    fn train_workers(&mut self) -> bool {
+        let in_production = self
+            .pending
+            .iter()
+            .filter(|(_, c)| matches!(c, Completion::Worker))
+            .count();
+        let ideal = ideal_workers(self.count(UID::CommandCenter), self.count(UID::Refinery));
+        let worker_cost = unit_cost(UID::SCV).expect("SCV has a known cost");
+        let time = self.time;
+        let mut wanted = ideal.saturating_sub(self.workers + in_production);
+        let mut supply_blocked = false;
+        for index in 0..self.structures.len() {
+            let townhall = &self.structures[index];
+            if wanted == 0 || !townhall.is_townhall() || townhall.free_slot(time).is_none() {
+                continue;
+            }
+            if !self.can_afford(worker_cost) {
+                break;
+            } else if self.supply_used + worker_cost.supply > self.supply_cap() {
+                supply_blocked = true;
+                break;
+            }
+            let finish = time + worker_cost.time;
+            self.structures[index].busy_until[0] = finish;
+            self.pending.push((finish, Completion::Worker));
+            self.spend(worker_cost);
+            wanted -= 1;
+        }
+        supply_blocked
+    }
+
+    /// Builds supply depots after the build order like `process_supply`
+    fn build_supply(&mut self) {
+        let time = self.time;
+        let supply_left = self.supply_cap().saturating_sub(self.supply_used);
+        let ordered = self
+            .structures
+            .iter()
+            .filter(|s| s.unit == UID::SupplyDepot && !s.is_ready(time))
+            .count();
+        let depot_cost = unit_cost(UID::SupplyDepot).expect("Supply depot has a known cost");
+        if time > END_OF_BUILD_PRIO
+            && ((supply_left < 5 && ordered == 0) || (supply_left < 2 && ordered == 1))
+            && self.can_afford(depot_cost)
+            && self.mining_workers() > 0
+        {
+            let finish = time + depot_cost.time;
+            self.builders.push(finish);
+            self.structures
+                .push(Structure::new(UID::SupplyDepot, finish));
+            self.spend(depot_cost);
+        }
+    }
+
+    fn track_supply_block(&mut self, supply_blocked: bool) {
+        match (supply_blocked, self.supply_blocked_since) {
+            (true, None) => self.supply_blocked_since = Some(self.time),
+            (false, Some(since)) => {
+                self.supply_blocks.push((since, self.time));
+                self.supply_blocked_since = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Collects resources for one time step, filling gas before minerals like `move_workers`
+    fn gather(&mut self) {
+        let time = self.time;
+        let ready_count = |unit| {
+            self.structures
+                .iter()
+                .filter(|s| s.is(unit) && s.is_ready(time))
+                .count()
+        };
+        let workers = self.mining_workers();
+        let gas_workers = workers.min(ready_count(UID::Refinery) * WORKERS_PER_GAS);
+        let mineral_workers =
+            (workers - gas_workers).min(ready_count(UID::CommandCenter) * WORKERS_PER_BASE);
+
+        self.minerals += mineral_workers as f32 * MINERAL_INCOME_PER_WORKER * TIME_STEP;
+        self.vespene += gas_workers as f32 * GAS_INCOME_PER_WORKER * TIME_STEP;
+
+        self.mineral_sum += self.minerals * TIME_STEP;
+        self.vespene_sum += self.vespene * TIME_STEP;
+        if self.minerals > FLOAT_THRESHOLD {
+            self.time_floating += TIME_STEP;
+        }
+    }
+
+    fn into_report(self, name: String, race: Race) -> SimReport {
+        let stalled_step = self
+            .step_error
+            .filter(|_| self.step_reports.len() < self.steps.len())
+            .map(|e| {
+                let index = self.step_reports.len();
+                (index, self.steps[index].unit, e)
+            });
+        let stalled_upgrade = self
+            .upgrade_error
+            .filter(|_| self.upgrade_reports.len() < self.upgrades.len());
+        SimReport {
+            name,
+            race,
+            steps: self.step_reports,
+            upgrades: self.upgrade_reports,
+            supply_blocks: self.supply_blocks,
+            average_minerals: self.mineral_sum / self.time.max(TIME_STEP),
+            average_vespene: self.vespene_sum / self.time.max(TIME_STEP),
+            time_floating: self.time_floating,
+            stalled_step,
+            stalled_upgrade,
+            end_time: self.time,
+        }
+    }
+}
+
+/// Returns how many workers `train_workers` trains for the bases and refineries
+pub(crate) fn ideal_workers(townhalls: usize, refineries: usize) -> usize {
+    MAX_WORKERS.min(townhalls * WORKERS_PER_BASE + refineries * WORKERS_PER_GAS)
+}
+
+impl fmt::Display for SimReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Simulated \"{}\" against {:?}", self.name, self.race)?;
+        writeln!(f, "{:>3}  {:<20} {:>6} {:>6}", "#", "Step", "Start", "Done")?;
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}  {:<20} {:>6} {:>6}",
+                i + 1,
+                format!("{:?}", step.unit),
+                format_time(step.start),
+                format_time(step.finish)
+            )?;
+        }
+        if let Some((index, unit, e)) = &self.stalled_step {
+            writeln!(f, "Stalled at step {} ({:?}): {:?}", index + 1, unit, e)?;
+        }
+
+        writeln!(f, "\n     {:<30} {:>6} {:>6}", "Upgrade", "Start", "Done")?;
+        for upgrade in &self.upgrades {
+            writeln!(
+                f,
+                "     {:<30} {:>6} {:>6}",
+                format!("{:?}", upgrade.upgrade),
+                format_time(upgrade.start),
+                format_time(upgrade.finish)
+            )?;
+        }
+        if let Some((upgrade, e)) = &self.stalled_upgrade {
+            writeln!(f, "Stalled at {:?}: {:?}", upgrade, e)?;
+        }
+
+        let blocked: f32 = self.supply_blocks.iter().map(|(from, to)| to - from).sum();
+        writeln!(f, "\nSupply blocked for {:.0}s:", blocked)?;
+        for (from, to) in &self.supply_blocks {
+            writeln!(
+                f,
+                "     {} - {} ({:.0}s)",
+                format_time(*from),
+                format_time(*to),
+                to - from
+            )?;
+        }
+        writeln!(
+            f,
+            "Unspent resources: {:.0} minerals and {:.0} gas on average, {:.0}s with more than {} minerals",
+            self.average_minerals, self.average_vespene, self.time_floating, FLOAT_THRESHOLD
+        )?;
+        write!(f, "Simulation ended at {}", format_time(self.end_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate_toml(contents: &str) -> SimReport {
+        simulate(&BuildOrder::parse(contents).unwrap(), Race::Terran)
+    }
+
+    #[test]
+    fn starts_a_14_depot_at_the_usual_time() {
+        let report = simulate_toml(
+            r#"
+            name = "Test"
+            steps = ["14 SupplyDepot"]
+            "#,
+        );
+        let depot = &report.steps[0];
+        // Around 0:18 in a real game
+        assert!((15.0..25.0).contains(&depot.start), "{:?}", depot);
+        assert_eq!(depot.finish - depot.start, 21.0);
+        assert!(report.stalled_step.is_none());
+    }
+
+    #[test]
+    fn waits_for_tech_requirements_and_addons() {
+        let report = simulate_toml(
+            r#"
+            name = "Test"
+            steps = ["14 SupplyDepot", "Barracks", "BarracksTechLab", "Marauder"]
+            upgrades = ["Stimpack"]
+            "#,
+        );
+        let [depot, barracks, techlab, marauder] = &report.steps[..] else {
+            panic!("not every step was started: {:?}", report.stalled_step);
+        };
+        assert!(barracks.start >= depot.finish);
+        assert!(techlab.start >= barracks.finish);
+        assert!(marauder.start >= techlab.finish);
+        let stim = &report.upgrades[0];
+        assert!(stim.start >= techlab.finish);
+        assert_eq!(stim.finish - stim.start, 100.0);
+    }
+
+    #[test]
+    fn reports_a_missing_addon() {
+        let report = simulate_toml(
+            r#"
+            name = "Test"
+            steps = ["SupplyDepot", "Barracks", "Marauder"]
+            "#,
+        );
+        assert!(matches!(report.stalled_step, Some((2, UID::Marauder, _))));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::bot::{format_time, TerranBot};
+use rust_sc2::prelude::*;
+use std::fmt;
+use UnitTypeId as UID;
+
+/// Guesses below this confidence do not satisfy build order conditions
+pub(crate) const MIN_STRATEGY_CONFIDENCE: f32 = 0.5;
+/// A Spawning Pool started before this game time is a 12 pool
+const TWELVE_POOL_START: f32 = 40.0;
+/// Zerglings seen before this game time come from a 12 pool
+const TWELVE_POOL_ZERGLINGS: f32 = 130.0;
+/// A townhall at the natural started before this game time is a fast expand
+const FAST_EXPAND_START: f32 = 90.0;
+/// Two gas buildings seen before this game time mean a fast tech
+const FAST_TECH_GAS: f32 = 120.0;
+/// A natural still empty when we see it after this game time means the enemy stays on one base
+const ONE_BASE_TIME: f32 = 165.0;
+/// Production structures seen before this game time closer to us than to the enemy are proxies
+const PROXY_TIME: f32 = 240.0;
+const POOL_BUILD_TIME: f32 = 46.0;
+const TOWNHALL_BUILD_TIME: f32 = 71.0;
+
+/// An opening the enemy is recognized to play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EnemyStrategy {
+    TwelvePool,
+    ProxyGate,
+    ProxyBarracks,
+    FastExpand,
+    /// No natural taken when it usually is, so an all-in is likely
+    OneBase,
+    /// Two gas buildings early, for fast tech or an early tech unit
+    FastTech,
+}
+
+impl EnemyStrategy {
+    const ALL: [Self; 6] = [
+        Self::TwelvePool,
+        Self::ProxyGate,
+        Self::ProxyBarracks,
+        Self::FastExpand,
+        Self::OneBase,
+        Self::FastTech,
+    ];
+
+    /// The name used in logs and build order conditions
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::TwelvePool => "12 pool",
+            Self::ProxyGate => "proxy gate",
+            Self::ProxyBarracks => "proxy barracks",
+            Self::FastExpand => "fast expand",
+            Self::OneBase => "one base",
+            Self::FastTech => "fast tech",
+        }
+    }
+
+    pub(crate) fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.label() == label)
+    }
+
+    /// Returns the labels of all strategies, for error messages
+    pub(crate) fn labels() -> String {
+        Self::ALL
+            .iter()
+            .map(|s| format!("\"{}\"", s.label()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl fmt::Display for EnemyStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// The enemy strategy we believe in, and how sure we are of it (0.0 to 1.0)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StrategyGuess {
+    pub(crate) strategy: EnemyStrategy,
+    pub(crate) confidence: f32,
+}
+
+impl TerranBot {
+    /// Classifies the enemy opening from what has been seen so far. The guess is only replaced
+    /// by a more confident one, or dropped once something seen since rules it out.
+    pub(crate) fn classify_enemy_strategy(&mut self) {
+        let time = format_time(self.time);
+        if let Some(current) = self
+            .enemy_strategy
+            .filter(|g| self.is_contradicted(g.strategy))
+        {
+            self.log(&format!(
+                "{} Enemy strategy: {} ruled out",
+                time, current.strategy
+            ));
+            self.enemy_strategy = None;
+        }
+
+        let Some(guess) = self
+            .strategy_evidence()
+            .into_iter()
+            .filter(|g| !self.is_contradicted(g.strategy))
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+        else {
+            return;
+        };
+        if self.enemy_strategy.is_some_and(|current| {
+            current.strategy == guess.strategy || current.confidence >= guess.confidence
+        }) {
+            return;
+        }
+        self.log(&format!(
+            "{} Enemy strategy: {} ({:.0}% confidence)",
+            time,
+            guess.strategy,
+            guess.confidence * 100.0
+        ));
+        self.enemy_strategy = Some(guess);
+    }
+
+    /// Returns true if we believe the enemy plays the strategy
+    pub(crate) fn is_enemy_strategy(&self, strategy: EnemyStrategy) -> bool {
+        self.enemy_strategy
+            .is_some_and(|g| g.strategy == strategy && g.confidence >= MIN_STRATEGY_CONFIDENCE)
+    }
+
+    /// Returns true if we believe the enemy goes for an early attack
+    pub(crate) fn is_enemy_rushing(&self) -> bool {
+        [
+            EnemyStrategy::TwelvePool,
+            EnemyStrategy::ProxyGate,
+            EnemyStrategy::ProxyBarracks,
+            EnemyStrategy::OneBase,
+        ]
+        .into_iter()
+        .any(|strategy| self.is_enemy_strategy(strategy))
+    }
+
+    /// Returns true if something seen rules the strategy out
+    fn is_contradicted(&self, strategy: EnemyStrategy) -> bool {
+        match strategy {
+            EnemyStrategy::OneBase => self.is_enemy_natural_taken() == Some(true),
+            EnemyStrategy::FastExpand => {
+                self.time > ONE_BASE_TIME && self.is_enemy_natural_taken() == Some(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns a guess for every strategy something seen points to
+    fn strategy_evidence(&self) -> Vec<StrategyGuess> {
+        let guess = |strategy, confidence| StrategyGuess {
+            strategy,
+            confidence,
+        };
+        let mut evidence = Vec::new();
+        let structures = self
+            .enemy_memory
+            .snapshots
+            .values()
+            .map(|s| (&s.unit, s.last_seen));
+
+        for (structure, last_seen) in structures.filter(|(u, _)| u.is_structure()) {
+            let progress = structure.build_progress();
+            match structure.type_id() {
+                UID::SpawningPool => {
+                    let started = last_seen - progress * POOL_BUILD_TIME;
+                    if started < TWELVE_POOL_START {
+                        // A finished pool may have been started even earlier than estimated
+                        evidence.push(guess(
+                            EnemyStrategy::TwelvePool,
+                            if progress < 1.0 { 0.9 } else { 0.7 },
+                        ));
+                    }
+                }
+                UID::Hatchery | UID::Nexus | UID::CommandCenter
+                    if self
+                        .enemy_natural()
+                        .is_some_and(|natural| structure.is_closer(2.0, natural)) =>
+                {
+                    let started = last_seen - progress * TOWNHALL_BUILD_TIME;
+                    if started < FAST_EXPAND_START {
+                        evidence.push(guess(EnemyStrategy::FastExpand, 0.8));
+                    }
+                }
+                UID::Gateway | UID::Pylon | UID::Barracks | UID::Factory | UID::Starport
+                    if last_seen < PROXY_TIME && self.is_proxy(structure.position()) =>
+                {
+                    let (strategy, confidence) = match structure.type_id() {
+                        // A pylon alone may be for a cannon rush or just vision
+                        UID::Pylon => (EnemyStrategy::ProxyGate, 0.6),
+                        UID::Gateway => (EnemyStrategy::ProxyGate, 0.9),
+                        _ => (EnemyStrategy::ProxyBarracks, 0.9),
+                    };
+                    evidence.push(guess(strategy, confidence));
+                }
+                _ => {}
+            }
+        }
+
+        let seen_before = |types: &[UID], before: f32| {
+            self.enemies_seen
+                .values()
+                .filter(|(unit, seen)| types.contains(unit) && *seen < before)
+                .count()
+        };
+        if seen_before(&[UID::Zergling], TWELVE_POOL_ZERGLINGS) > 0 {
+            evidence.push(guess(EnemyStrategy::TwelvePool, 0.9));
+        }
+        if seen_before(
+            &[UID::Extractor, UID::Assimilator, UID::Refinery],
+            FAST_TECH_GAS,
+        ) >= 2
+        {
+            evidence.push(guess(EnemyStrategy::FastTech, 0.6));
+        }
+
+        if self.time > ONE_BASE_TIME && self.is_enemy_natural_taken() == Some(false) {
+            evidence.push(guess(EnemyStrategy::OneBase, 0.6));
+        }
+        evidence
+    }
+
+    /// Returns whether the enemy has a townhall at their natural, `None` if we have not seen it
+    /// and do not remember one there
+    fn is_enemy_natural_taken(&self) -> Option<bool> {
+        let natural = self.enemy_natural()?;
+        let taken = self
+            .enemy_memory
+            .structures()
+            .iter()
+            .any(|s| s.type_id().is_townhall() && s.is_closer(2.0, natural));
+        (taken || self.is_visible(natural)).then_some(taken)
+    }
+
+    /// Returns true if the location is closer to our start location than to the enemy's
+    fn is_proxy(&self, location: Point2) -> bool {
+        location.distance(self.start_location) < location.distance(self.enemy_start)
+    }
+
+    /// Returns the expansion closest to the enemy start location, other than their main
+    pub(crate) fn enemy_natural(&self) -> Option<Point2> {
+        self.expansions
+            .iter()
+            .map(|e| e.loc)
+            .filter(|&loc| loc.distance(self.enemy_start) > 5.0)
+            .min_by(|a, b| {
+                a.distance(self.enemy_start)
+                    .total_cmp(&b.distance(self.enemy_start))
+            })
+    }
+}
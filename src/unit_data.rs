@@ -0,0 +1,253 @@
+//! Static Terran costs and requirements, for when the game data from the API isn't available
+//! (e.g. when simulating a build order offline).
+
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Terran structures below this health burn down unless repaired
+pub(crate) const BURNING_HEALTH: f32 = 1.0 / 3.0;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BuildCost {
+    pub(crate) minerals: u32,
+    pub(crate) vespene: u32,
+    pub(crate) supply: u32,
+    /// Build or research time in game seconds
+    pub(crate) time: f32,
+}
+
+const fn cost(minerals: u32, vespene: u32, supply: u32, time: f32) -> BuildCost {
+    BuildCost {
+        minerals,
+        vespene,
+        supply,
+        time,
+    }
+}
+
+/// Returns the cost of a Terran unit or structure. Morphs and addons cost only the difference.
+pub(crate) fn unit_cost(unit: UID) -> Option<BuildCost> {
+    Some(match unit {
+        UID::SCV => cost(50, 0, 1, 12.0),
+        UID::Marine => cost(50, 0, 1, 18.0),
+        UID::Reaper => cost(50, 50, 1, 32.0),
+        UID::Marauder => cost(100, 25, 2, 21.0),
+        UID::Ghost => cost(150, 125, 2, 29.0),
+        UID::Hellion | UID::HellionTank => cost(100, 0, 2, 21.0),
+        UID::WidowMine => cost(75, 25, 2, 21.0),
+        UID::Cyclone => cost(150, 100, 3, 32.0),
+        UID::SiegeTank => cost(150, 125, 3, 32.0),
+        UID::Thor => cost(300, 200, 6, 43.0),
+        UID::VikingFighter => cost(150, 75, 2, 30.0),
+        UID::Medivac => cost(100, 100, 2, 30.0),
+        UID::Liberator => cost(150, 125, 3, 43.0),
+        UID::Raven => cost(100, 150, 2, 34.0),
+        UID::Banshee => cost(150, 100, 3, 43.0),
+        UID::Battlecruiser => cost(400, 300, 6, 64.0),
+        UID::CommandCenter => cost(400, 0, 0, 71.0),
+        UID::OrbitalCommand => cost(150, 0, 0, 25.0),
+        UID::PlanetaryFortress => cost(150, 150, 0, 36.0),
+        UID::SupplyDepot => cost(100, 0, 0, 21.0),
+        UID::Refinery => cost(75, 0, 0, 21.0),
+        UID::Barracks => cost(150, 0, 0, 46.0),
+        UID::EngineeringBay => cost(125, 0, 0, 25.0),
+        UID::Bunker => cost(100, 0, 0, 29.0),
+        UID::MissileTurret => cost(100, 0, 0, 18.0),
+        UID::SensorTower => cost(125, 50, 0, 18.0),
+        UID::Factory => cost(150, 100, 0, 43.0),
+        UID::GhostAcademy => cost(150, 50, 0, 29.0),
+        UID::Armory => cost(150, 100, 0, 46.0),
+        UID::Starport => cost(150, 100, 0, 36.0),
+        UID::FusionCore => cost(150, 150, 0, 46.0),
+        UID::BarracksTechLab | UID::FactoryTechLab | UID::StarportTechLab => cost(50, 25, 0, 18.0),
+        UID::BarracksReactor | UID::FactoryReactor | UID::StarportReactor => cost(50, 50, 0, 36.0),
+        _ => return None,
+    })
+}
+
+/// Returns the cost of a Terran upgrade
+pub(crate) fn upgrade_cost(upgrade: UpgradeId) -> Option<BuildCost> {
+    Some(match upgrade {
+        UpgradeId::Stimpack => cost(100, 100, 0, 100.0),
+        UpgradeId::ShieldWall => cost(100, 100, 0, 79.0),
+        UpgradeId::PunisherGrenades => cost(50, 50, 0, 43.0),
+        UpgradeId::DrillClaws => cost(75, 75, 0, 79.0),
+        UpgradeId::SmartServos => cost(100, 100, 0, 79.0),
+        UpgradeId::HighCapacityBarrels => cost(100, 100, 0, 79.0),
+        UpgradeId::BansheeCloak => cost(100, 100, 0, 86.0),
+        UpgradeId::BansheeSpeed => cost(125, 125, 0, 100.0),
+        UpgradeId::RavenCorvidReactor => cost(150, 150, 0, 79.0),
+        UpgradeId::BattlecruiserEnableSpecializations => cost(150, 150, 0, 100.0),
+        UpgradeId::LiberatorAGRangeUpgrade => cost(150, 150, 0, 79.0),
+        UpgradeId::PersonalCloaking => cost(150, 150, 0, 86.0),
+        UpgradeId::EnhancedShockwaves => cost(150, 150, 0, 79.0),
+        UpgradeId::HiSecAutoTracking => cost(100, 100, 0, 57.0),
+        UpgradeId::TerranBuildingArmor => cost(150, 150, 0, 100.0),
+        UpgradeId::TerranInfantryWeaponsLevel1 | UpgradeId::TerranInfantryArmorsLevel1 => {
+            cost(100, 100, 0, 114.0)
+        }
+        UpgradeId::TerranInfantryWeaponsLevel2 | UpgradeId::TerranInfantryArmorsLevel2 => {
+            cost(175, 175, 0, 136.0)
+        }
+        UpgradeId::TerranInfantryWeaponsLevel3 | UpgradeId::TerranInfantryArmorsLevel3 => {
+            cost(250, 250, 0, 157.0)
+        }
+        UpgradeId::TerranVehicleWeaponsLevel1
+        | UpgradeId::TerranShipWeaponsLevel1
+        | UpgradeId::TerranVehicleAndShipArmorsLevel1 => cost(100, 100, 0, 114.0),
+        UpgradeId::TerranVehicleWeaponsLevel2
+        | UpgradeId::TerranShipWeaponsLevel2
+        | UpgradeId::TerranVehicleAndShipArmorsLevel2 => cost(175, 175, 0, 136.0),
+        UpgradeId::TerranVehicleWeaponsLevel3
+        | UpgradeId::TerranShipWeaponsLevel3
+        | UpgradeId::TerranVehicleAndShipArmorsLevel3 => cost(250, 250, 0, 157.0),
+        _ => return None,
+    })
+}
+
+/// Returns the supply provided by a finished structure
+pub(crate) fn supply_provided(structure: UID) -> u32 {
+    match structure {
+        UID::CommandCenter | UID::OrbitalCommand | UID::PlanetaryFortress => 15,
+        UID::SupplyDepot => 8,
+        _ => 0,
+    }
+}
+
+/// Returns the structure and every structure which counts as it: the forms it takes when lowered
+/// or lifted off, its rich gas version and, for a Command Center, the townhalls it morphs into
+pub(crate) fn type_aliases(structure: UID) -> Vec<UID> {
+    let aliases: &[UID] = match structure {
+        UID::SupplyDepot => &[UID::SupplyDepotLowered],
+        UID::Refinery => &[UID::RefineryRich],
+        UID::Barracks => &[UID::BarracksFlying],
+        UID::Factory => &[UID::FactoryFlying],
+        UID::Starport => &[UID::StarportFlying],
+        UID::CommandCenter => &[
+            UID::CommandCenterFlying,
+            UID::OrbitalCommand,
+            UID::OrbitalCommandFlying,
+            UID::PlanetaryFortress,
+        ],
+        UID::OrbitalCommand => &[UID::OrbitalCommandFlying],
+        _ => &[],
+    };
+    [structure]
+        .into_iter()
+        .chain(aliases.iter().copied())
+        .collect()
+}
+
+/// Returns the addon the producer needs to have to produce `unit`
+pub(crate) fn required_addon(unit: UID) -> Option<UID> {
+    match unit {
+        UID::Marauder | UID::Ghost => Some(UID::BarracksTechLab),
+        UID::SiegeTank | UID::Thor => Some(UID::FactoryTechLab),
+        UID::Raven | UID::Banshee | UID::Battlecruiser => Some(UID::StarportTechLab),
+        _ => None,
+    }
+}
+
+/// Something which must exist before a research can be started
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Requirement {
+    Structure(UID),
+    Upgrade(UpgradeId),
+}
+
+/// Requirements of each research, besides an idle researcher from `RESEARCHERS`
+const UPGRADE_REQUIREMENTS: &[(UpgradeId, Requirement)] = {
+    use Requirement::{Structure, Upgrade};
+    use UpgradeId as U;
+    &[
+        // Engineering Bay
+        (U::TerranInfantryWeaponsLevel2, Structure(UID::Armory)),
+        (
+            U::TerranInfantryWeaponsLevel2,
+            Upgrade(U::TerranInfantryWeaponsLevel1),
+        ),
+        (U::TerranInfantryWeaponsLevel3, Structure(UID::Armory)),
+        (
+            U::TerranInfantryWeaponsLevel3,
+            Upgrade(U::TerranInfantryWeaponsLevel2),
+        ),
+        (U::TerranInfantryArmorsLevel2, Structure(UID::Armory)),
+        (
+            U::TerranInfantryArmorsLevel2,
+            Upgrade(U::TerranInfantryArmorsLevel1),
+        ),
+        (U::TerranInfantryArmorsLevel3, Structure(UID::Armory)),
+        (
+            U::TerranInfantryArmorsLevel3,
+            Upgrade(U::TerranInfantryArmorsLevel2),
+        ),
+        // Armory
+        (
+            U::TerranVehicleWeaponsLevel2,
+            Upgrade(U::TerranVehicleWeaponsLevel1),
+        ),
+        (
+            U::TerranVehicleWeaponsLevel3,
+            Upgrade(U::TerranVehicleWeaponsLevel2),
+        ),
+        (
+            U::TerranShipWeaponsLevel2,
+            Upgrade(U::TerranShipWeaponsLevel1),
+        ),
+        (
+            U::TerranShipWeaponsLevel3,
+            Upgrade(U::TerranShipWeaponsLevel2),
+        ),
+        (
+            U::TerranVehicleAndShipArmorsLevel2,
+            Upgrade(U::TerranVehicleAndShipArmorsLevel1),
+        ),
+        (
+            U::TerranVehicleAndShipArmorsLevel3,
+            Upgrade(U::TerranVehicleAndShipArmorsLevel2),
+        ),
+        // Factory TechLab
+        (U::DrillClaws, Structure(UID::Armory)),
+        (U::SmartServos, Structure(UID::Armory)),
+        // Fusion Core
+        (
+            U::BattlecruiserEnableSpecializations,
+            Structure(UID::FusionCore),
+        ),
+        (U::LiberatorAGRangeUpgrade, Structure(UID::FusionCore)),
+    ]
+};
+
+/// Returns everything which must exist before `upgrade` can be researched
+pub(crate) fn upgrade_requirements(upgrade: UpgradeId) -> impl Iterator<Item = Requirement> {
+    UPGRADE_REQUIREMENTS
+        .iter()
+        .filter(move |(u, _)| *u == upgrade)
+        .map(|&(_, requirement)| requirement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rich_refineries_count_as_refineries() {
+        assert!(type_aliases(UID::Refinery).contains(&UID::RefineryRich));
+    }
+
+    #[test]
+    fn morphed_townhalls_count_as_command_centers() {
+        let aliases = type_aliases(UID::CommandCenter);
+        assert!(aliases.contains(&UID::OrbitalCommand));
+        assert!(aliases.contains(&UID::PlanetaryFortress));
+        assert!(aliases.contains(&UID::OrbitalCommandFlying));
+        assert!(!type_aliases(UID::OrbitalCommand).contains(&UID::PlanetaryFortress));
+    }
+
+    #[test]
+    fn lowered_and_lifted_structures_count() {
+        assert!(type_aliases(UID::SupplyDepot).contains(&UID::SupplyDepotLowered));
+        assert!(type_aliases(UID::Barracks).contains(&UID::BarracksFlying));
+        assert_eq!(type_aliases(UID::Bunker), [UID::Bunker]);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::{
+    build_order::{producer_of, BuildEntry, BuildOrder, BuildStep, Trigger},
+    simulator::ideal_workers,
+    unit_data::{required_addon, supply_provided, unit_cost},
+};
+use rust_sc2::prelude::*;
+use rustc_hash::FxHashMap;
+use std::fmt;
+use UnitTypeId as UID;
+
+/// Why a build order step or upgrade can never be started
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Problem {
+    /// The structure required by `TECH_REQUIREMENTS` is not built by an earlier step
+    MissingTechRequirement(UID),
+    /// No earlier step builds a producer without an addon (or a Command Center to morph)
+    MissingProducer(UID),
+    /// The producer needs this addon, which is not built by an earlier step
+    MissingAddon(UID),
+    /// Every geyser at our bases already has a refinery
+    NoFreeGeyser,
+    /// The step needs more supply than earlier steps provide
+    SupplyBlocked { needed: u32, available: u32 },
+    /// The supply trigger is higher than the supply cap provided by earlier steps
+    UnreachableSupply { trigger: u32, available: u32 },
+    /// The build progress trigger waits for a structure no earlier step builds
+    MissingTriggerStructure(UID),
+    /// The upgrade is researched at a structure no step builds
+    MissingResearcher(UID),
+}
+
+/// Where in a build order a problem was found. Steps are numbered from 1, in the order they are
+/// written in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Entry {
+    Step(usize, UID),
+    Upgrade(UpgradeId),
+}
+
+/// A problem found with a step or an upgrade of a build order
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Issue {
+    pub(crate) entry: Entry,
+    pub(crate) problem: Problem,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.entry {
+            Entry::Step(step, unit) => write!(f, "step {} ({:?}): ", step, unit)?,
+            Entry::Upgrade(upgrade) => write!(f, "upgrade {:?}: ", upgrade)?,
+        }
+        match self.problem {
+            Problem::MissingTechRequirement(requirement) => write!(
+                f,
+                "requires a {:?}, which no earlier step builds",
+                requirement
+            ),
+            Problem::MissingProducer(producer) => write!(
+                f,
+                "needs a free {:?} to be produced from, which no earlier step builds",
+                producer
+            ),
+            Problem::MissingAddon(addon) => {
+                write!(f, "needs a {:?}, which no earlier step builds", addon)
+            }
+            Problem::NoFreeGeyser => write!(f, "all geysers at our bases are already taken"),
+            Problem::SupplyBlocked { needed, available } => write!(
+                f,
+                "needs {} supply but earlier steps only provide {}",
+                needed, available
+            ),
+            Problem::UnreachableSupply { trigger, available } => write!(
+                f,
+                "waits for {} supply but earlier steps only provide {}",
+                trigger, available
+            ),
+            Problem::MissingTriggerStructure(structure) => write!(
+                f,
+                "waits for a {:?}, which no earlier step builds",
+                structure
+            ),
+            Problem::MissingResearcher(researcher) => write!(
+                f,
+                "is researched at a {:?}, which no step builds",
+                researcher
+            ),
+        }
+    }
+}
+
+/// What is guaranteed to be built after the steps walked so far. SCVs are trained between the
+/// steps like in the simulator, filling the supply the steps leave free.
+#[derive(Clone)]
+struct State {
+    built: FxHashMap<UID, usize>,
+    supply_used: u32,
+    supply_cap: u32,
+}
+
+impl State {
+    fn count(&self, unit: UID) -> usize {
+        self.built.get(&unit).copied().unwrap_or_default()
+    }
+
+    /// Trains SCVs until the bases are saturated or the supply is used up
+    fn train_workers(&mut self) {
+        let ideal = ideal_workers(self.count(UID::CommandCenter), self.count(UID::Refinery));
+        let trained = ideal
+            .saturating_sub(self.count(UID::SCV))
+            .min(self.supply_cap.saturating_sub(self.supply_used) as usize);
+        *self.built.entry(UID::SCV).or_default() += trained;
+        self.supply_used += trained as u32;
+    }
+
+    /// Returns what is built on both of two paths through a branch
+    fn intersect(&self, other: &State) -> State {
+        State {
+            built: self
+                .built
+                .iter()
+                .map(|(&unit, &count)| (unit, count.min(other.count(unit))))
+                .collect(),
+            supply_used: self.supply_used.max(other.supply_used),
+            supply_cap: self.supply_cap.min(other.supply_cap),
+        }
+    }
+}
+
+/// Walks the build order and reports every step which can never be started by
+/// `build_next_in_build_order`, which would otherwise stall the bot. Both paths of every
+/// branch are checked. Upgrades are reported when their researcher is not built on every path.
+pub(crate) fn validate(build_order: &BuildOrder) -> Vec<Issue> {
+    let mut state = State {
+        built: [(UID::CommandCenter, 1), (UID::SCV, 12)]
+            .into_iter()
+            .collect(),
+        supply_used: 12,
+        supply_cap: supply_provided(UID::CommandCenter),
+    };
+    let mut step = 0;
+    let mut issues = Vec::new();
+    validate_entries(&build_order.entries, &mut state, &mut step, &mut issues);
+    for &upgrade in &build_order.upgrades {
+        if let Some(&researcher) = RESEARCHERS
+            .get(&upgrade)
+            .filter(|&&researcher| state.count(researcher) == 0)
+        {
+            issues.push(Issue {
+                entry: Entry::Upgrade(upgrade),
+                problem: Problem::MissingResearcher(researcher),
+            });
+        }
+    }
+    issues
+}
+
+fn validate_entries(
+    entries: &[BuildEntry],
+    state: &mut State,
+    step: &mut usize,
+    issues: &mut Vec<Issue>,
+) {
+    for entry in entries {
+        // Numbered the same way as when parsing, so the numbers match the file
+        *step += 1;
+        match entry {
+            BuildEntry::Step(build_step) => {
+                state.train_workers();
+                issues.extend(check_step(build_step, state).map(|problem| Issue {
+                    entry: Entry::Step(*step, build_step.unit),
+                    problem,
+                }));
+                // Count the step as built even if it has a problem, so only the root cause of
+                // a problem is reported
+                build(build_step.unit, state);
+            }
+            BuildEntry::Branch(branch) => {
+                let mut then = state.clone();
+                validate_entries(&branch.then, &mut then, step, issues);
+                let mut otherwise = state.clone();
+                validate_entries(&branch.otherwise, &mut otherwise, step, issues);
+                *state = then.intersect(&otherwise);
+            }
+        }
+    }
+}
+
+fn check_step(step: &BuildStep, state: &State) -> Option<Problem> {
+    let unit = step.unit;
+    match step.trigger {
+        Some(Trigger::Supply(trigger)) if trigger > state.supply_cap => {
+            return Some(Problem::UnreachableSupply {
+                trigger,
+                available: state.supply_cap,
+            });
+        }
+        Some(Trigger::Progress(structure, _)) if state.count(structure) == 0 => {
+            return Some(Problem::MissingTriggerStructure(structure));
+        }
+        _ => {}
+    }
+
+    if let Some(&requirement) = TECH_REQUIREMENTS.get(&unit) {
+        if state.count(requirement) == 0 {
+            return Some(Problem::MissingTechRequirement(requirement));
+        }
+    }
+
+    let producer = producer_of(unit)?;
+    let addons = |producer: UID| match producer {
+        UID::Barracks => state.count(UID::BarracksReactor) + state.count(UID::BarracksTechLab),
+        UID::Factory => state.count(UID::FactoryReactor) + state.count(UID::FactoryTechLab),
+        UID::Starport => state.count(UID::StarportReactor) + state.count(UID::StarportTechLab),
+        _ => 0,
+    };
+    if unit.is_addon() && state.count(producer) <= addons(producer) {
+        return Some(Problem::MissingProducer(producer));
+    } else if producer == UID::CommandCenter
+        && unit != UID::SCV
+        && state.count(UID::CommandCenter)
+            <= state.count(UID::OrbitalCommand) + state.count(UID::PlanetaryFortress)
+    {
+        return Some(Problem::MissingProducer(producer));
+    } else if state.count(producer) == 0 {
+        return Some(Problem::MissingProducer(producer));
+    }
+    if let Some(addon) = required_addon(unit).filter(|&addon| state.count(addon) == 0) {
+        return Some(Problem::MissingAddon(addon));
+    }
+    if unit == UID::Refinery && state.count(UID::Refinery) >= 2 * state.count(UID::CommandCenter) {
+        return Some(Problem::NoFreeGeyser);
+    }
+
+    let supply = unit_cost(unit).map_or(0, |cost| cost.supply);
+    if supply > 0 && state.supply_used + supply > state.supply_cap {
+        return Some(Problem::SupplyBlocked {
+            needed: state.supply_used + supply,
+            available: state.supply_cap,
+        });
+    }
+    None
+}
+
+fn build(unit: UID, state: &mut State) {
+    *state.built.entry(unit).or_default() += 1;
+    state.supply_used += unit_cost(unit).map_or(0, |cost| cost.supply);
+    // Morphing a Command Center does not add any supply
+    if !matches!(unit, UID::OrbitalCommand | UID::PlanetaryFortress) {
+        state.supply_cap = (state.supply_cap + supply_provided(unit)).min(200);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate_steps(steps: &str) -> Vec<Issue> {
+        let contents = format!("name = \"Test\"\nsteps = {}", steps);
+        validate(&BuildOrder::parse(&contents).unwrap())
+    }
+
+    #[test]
+    fn reports_raven_before_starport_techlab() {
+        let issues =
+            validate_steps(r#"["SupplyDepot", "Barracks", "Factory", "Starport", "Raven"]"#);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].entry, Entry::Step(5, UID::Raven));
+        assert!(issues[0].to_string().contains("StarportTechLab"));
+
+        let issues = validate_steps(
+            r#"["SupplyDepot", "Barracks", "Factory", "Starport", "StarportTechLab", "Raven"]"#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn reports_cyclone_without_factory() {
+        let issues = validate_steps(r#"["SupplyDepot", "Barracks", "Cyclone"]"#);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].entry, Entry::Step(3, UID::Cyclone));
+        assert!(issues[0].to_string().contains("Factory"));
+    }
+
+    #[test]
+    fn reports_both_paths_of_a_branch() {
+        let issues = validate_steps(
+            r#"["SupplyDepot", { if = { race = "Zerg" }, then = ["Barracks"], else = ["Bunker"] }, "Marine"]"#,
+        );
+        assert_eq!(
+            issues,
+            [
+                Issue {
+                    entry: Entry::Step(4, UID::Bunker),
+                    problem: Problem::MissingTechRequirement(UID::Barracks),
+                },
+                Issue {
+                    entry: Entry::Step(5, UID::Marine),
+                    problem: Problem::MissingProducer(UID::Barracks),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_the_supply_of_workers() {
+        // 22 SCVs for a base with two refineries and a Marine leave no room for another Marine
+        let issues = validate_steps(
+            r#"["SupplyDepot", "Barracks", "Refinery", "Refinery", "Marine", "Marine"]"#,
+        );
+        assert_eq!(
+            issues,
+            [Issue {
+                entry: Entry::Step(6, UID::Marine),
+                problem: Problem::SupplyBlocked {
+                    needed: 24,
+                    available: 23
+                },
+            }]
+        );
+
+        let issues = validate_steps(
+            r#"["SupplyDepot", "Barracks", "Refinery", "Refinery", "Marine", "SupplyDepot", "Marine"]"#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn reports_upgrades_without_researcher() {
+        let contents = r#"
+            name = "Test"
+            steps = ["SupplyDepot", "Barracks", "BarracksTechLab"]
+            upgrades = ["Stimpack", "TerranInfantryWeaponsLevel1"]
+        "#;
+        let issues = validate(&BuildOrder::parse(contents).unwrap());
+        assert_eq!(
+            issues,
+            [Issue {
+                entry: Entry::Upgrade(UpgradeId::TerranInfantryWeaponsLevel1),
+                problem: Problem::MissingResearcher(UID::EngineeringBay),
+            }]
+        );
+        assert!(issues[0].to_string().contains("EngineeringBay"));
+    }
+
+    #[test]
+    fn shipped_build_orders_are_valid() {
+        for (name, contents) in [
+            ("tvt", include_str!("../build_orders/tvt.toml")),
+            ("tvp", include_str!("../build_orders/tvp.toml")),
+            ("tvz", include_str!("../build_orders/tvz.toml")),
+            ("tvr", include_str!("../build_orders/tvr.toml")),
+        ] {
+            let issues = validate(&BuildOrder::parse(contents).unwrap());
+            assert!(issues.is_empty(), "{}: {:?}", name, issues);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use crate::bot::TerranBot;
+use rust_sc2::prelude::*;
+use UnitTypeId as UID;
+
+/// Positions of the depot/barracks/depot wall at the top of the main ramp
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RampWall {
+    pub(crate) depots: [Point2; 2],
+    /// Placed so the Barracks still has room for an addon
+    pub(crate) barracks: Point2,
+}
+
+impl TerranBot {
+    /// Computes the wall at the main ramp, `None` if the ramp has no standard wall
+    pub(crate) fn plan_ramp_wall(&self) -> Option<RampWall> {
+        let ramp = &self.ramps.my;
+        Some(RampWall {
+            depots: ramp.corner_depots()?,
+            barracks: ramp.barracks_correct_placement()?,
+        })
+    }
+
+    /// Returns a free spot in the ramp wall for the structure. Walls are built from the first
+    /// depots and the first Barracks, later ones are placed in the base.
+    pub(crate) fn find_wall_location(&self, structure: UID) -> Option<Point2> {
+        let wall = self.ramp_wall?;
+        let spots: &[Point2] = match structure {
+            UID::SupplyDepot => &wall.depots,
+            UID::Barracks if self.counter().all().count(UID::Barracks) == 0 => {
+                std::slice::from_ref(&wall.barracks)
+            }
+            _ => return None,
+        };
+        spots.iter().copied().find(|&spot| {
+            !self.is_slot_taken(spot)
+                && !self.is_failed_location(spot)
+                && self.can_place(structure, spot)
+        })
+    }
+}
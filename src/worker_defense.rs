@@ -0,0 +1,182 @@
+use crate::bot::{Tag, TerranBot};
+use rust_sc2::prelude::*;
+use rustc_hash::FxHashSet;
+use UnitTypeId as UID;
+
+/// Enemies within this distance of a townhall threaten its workers
+const DEFENSE_RADIUS: f32 = 12.0;
+/// A lone enemy worker is only chased when it comes this close to a townhall
+const MINERAL_LINE_RADIUS: f32 = 8.0;
+/// Defenders below this health go back to mining
+const MIN_DEFENDER_HEALTH: f32 = 0.3;
+/// Harassment units which SCVs cannot catch or hit, so they flee from them instead
+const FLEE_FROM: &[UID] = &[
+    UID::Reaper,
+    UID::Adept,
+    UID::Hellion,
+    UID::Oracle,
+    UID::Banshee,
+    UID::Mutalisk,
+];
+
+/// What a worker pulled off mining is doing, and the townhall it was pulled at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DefenseRole {
+    Fight(Tag),
+    Flee(Tag),
+}
+
+impl TerranBot {
+    /// Pulls workers to fight enemies attacking a mineral line, or lets them flee to another base
+    /// when they cannot win, and sends them back to mining once the threat is gone
+    pub(crate) fn defend_workers(&mut self) {
+        let threatened: Vec<_> = self
+            .units
+            .my
+            .townhalls
+            .iter()
+            .ready()
+            .filter_map(|townhall| {
+                let threats = self.worker_threats(townhall);
+                (!threats.is_empty()).then(|| (townhall.tag(), threats))
+            })
+            .collect();
+        self.release_defenders(&threatened.iter().map(|(t, _)| *t).collect());
+
+        for (townhall, threats) in threatened {
+            let Some(base) = self.units.my.townhalls.get(townhall) else {
+                continue;
+            };
+            let enemies = self.units.enemy.units.find_tags(&threats);
+            let can_fight = enemies
+                .iter()
+                .all(|e| !e.is_flying() && !FLEE_FROM.contains(&e.type_id()));
+            let needed: usize = enemies.iter().map(defenders_needed).sum();
+            let miners: Vec<_> = self
+                .units
+                .my
+                .workers
+                .iter()
+                .of_type(self.race_values.worker)
+                .closer(DEFENSE_RADIUS, base)
+                .filter(|w| !w.is_constructing() && !self.defending_workers.contains_key(&w.tag()))
+                .filter(|w| !self.is_scout(w.tag()))
+                .collect();
+            let defenders: Vec<_> = self
+                .defending_workers
+                .iter()
+                .filter(|&(_, &role)| role == DefenseRole::Fight(townhall))
+                .map(|(&worker, _)| worker)
+                .collect();
+
+            let refuge = self
+                .units
+                .my
+                .townhalls
+                .iter()
+                .ready()
+                .filter(|t| t.tag() != townhall && self.worker_threats(t).is_empty())
+                .closest(base);
+            let refuge_mineral =
+                refuge.and_then(|refuge| self.units.mineral_fields.closest(refuge.position()));
+            let outnumbered = needed > miners.len() + defenders.len();
+            let mut roles = Vec::new();
+            match refuge_mineral {
+                Some(mineral) if !can_fight || outnumbered => {
+                    // We would lose the fight, so get the miners out of there
+                    for miner in miners
+                        .iter()
+                        .filter(|m| enemies.iter().any(|e| e.is_closer(DEFENSE_RADIUS, **m)))
+                    {
+                        miner.gather(mineral.tag(), false);
+                        roles.push((miner.tag(), DefenseRole::Flee(townhall)));
+                    }
+                }
+                _ if can_fight => {
+                    let mut miners = miners;
+                    miners.sort_by(|a, b| {
+                        b.health_percentage()
+                            .partial_cmp(&a.health_percentage())
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    let pulled = needed.saturating_sub(defenders.len());
+                    for miner in miners.into_iter().take(pulled) {
+                        roles.push((miner.tag(), DefenseRole::Fight(townhall)));
+                    }
+                    let fighters = self
+                        .units
+                        .my
+                        .workers
+                        .find_tags(defenders.iter().chain(roles.iter().map(|(w, _)| w)));
+                    for defender in &fighters {
+                        if let Some(enemy) = enemies
+                            .closest(defender)
+                            .filter(|e| defender.target_tag() != Some(e.tag()))
+                        {
+                            defender.attack(Target::Tag(enemy.tag()), false);
+                        }
+                    }
+                }
+                // Nowhere to flee to from enemies we cannot fight, so keep mining
+                _ => {}
+            }
+            self.defending_workers.extend(roles);
+        }
+    }
+
+    /// Returns the enemies attacking the workers of a townhall. A lone enemy worker is only a
+    /// threat inside the mineral line, and only needs a single defender.
+    fn worker_threats(&self, townhall: &Unit) -> Vec<Tag> {
+        let threats: Vec<_> = self
+            .units
+            .enemy
+            .units
+            .iter()
+            .closer(DEFENSE_RADIUS, townhall)
+            .filter(|e| e.can_attack_ground() || e.type_id() == UID::Oracle)
+            .collect();
+        if let [scout] = threats.as_slice() {
+            if scout.type_id().is_worker() && !scout.is_closer(MINERAL_LINE_RADIUS, townhall) {
+                return Vec::new();
+            }
+        }
+        threats.iter().map(|e| e.tag()).collect()
+    }
+
+    /// Sends workers back to mining once their base is no longer threatened, or when they are
+    /// about to die
+    fn release_defenders(&mut self, threatened: &FxHashSet<Tag>) {
+        let released: Vec<_> = self
+            .defending_workers
+            .iter()
+            .filter(|&(&worker, &role)| {
+                let base = match role {
+                    DefenseRole::Fight(base) | DefenseRole::Flee(base) => base,
+                };
+                !threatened.contains(&base)
+                    || self.units.my.workers.get(worker).is_none_or(|w| {
+                        role == DefenseRole::Fight(base)
+                            && w.health_percentage()
+                                .is_some_and(|h| h < MIN_DEFENDER_HEALTH)
+                    })
+            })
+            .map(|(&worker, _)| worker)
+            .collect();
+        for worker in released {
+            self.defending_workers.remove(&worker);
+            if let Some(worker) = self.units.my.workers.get(worker) {
+                // Idle workers are given a mineral patch by `assign_mineral_workers`
+                worker.stop(false);
+            }
+        }
+    }
+}
+
+/// Returns how many SCVs are needed to fight the enemy
+fn defenders_needed(enemy: &Unit) -> usize {
+    if enemy.type_id().is_worker() {
+        1
+    } else {
+        (enemy.supply_cost() * 2.0).ceil() as usize
+    }
+}